@@ -0,0 +1,121 @@
+//! Compares the day 2 record parser against the original regex-per-line implementation on a
+//! generated million-line game log. Run with `cargo bench --bench day2_parse`.
+
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use rust::day2::{Game, Hand};
+
+const NUM_LINES: usize = 1_000_000;
+
+/// A small xorshift generator so the log is the same on every run.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self, bound: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % bound
+    }
+}
+
+fn generate_log() -> Vec<String> {
+    let mut rng = XorShift(0x2023_1202);
+    let colours = ["red", "green", "blue"];
+    (1..=NUM_LINES)
+        .map(|id| {
+            let hands = (0..1 + rng.next(6))
+                .map(|_| {
+                    let start = rng.next(3) as usize;
+                    (0..1 + rng.next(3) as usize)
+                        .map(|i| format!("{} {}", 1 + rng.next(20), colours[(start + i) % 3]))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                })
+                .collect::<Vec<String>>()
+                .join("; ");
+            format!("Game {id}: {hands}")
+        })
+        .collect()
+}
+
+/// A hand as it was stored before colours were interned, with a field for each fixed colour.
+struct RegexHand {
+    num_green: u32,
+    num_red: u32,
+    num_blue: u32,
+}
+
+struct RegexGame {
+    id: usize,
+    hands: Vec<RegexHand>,
+}
+
+/// The parser as it was before the byte-level rewrite: two regexes compiled for every line, and
+/// each entry stored straight into a fixed field.
+fn parse_with_regex(value: &str) -> RegexGame {
+    let re_game_id = Regex::new(r"Game ([0-9]*)").unwrap();
+    let re_entry = Regex::new(r"([0-9]*) (blue|green|red)").unwrap();
+    let values = value.split(": ").collect::<Vec<&str>>();
+    let game_id: &str = &re_game_id.captures(values[0]).unwrap()[1];
+    let mut hands = vec![];
+    for hand in values[1].split(';') {
+        let mut new_hand = RegexHand {
+            num_green: 0,
+            num_red: 0,
+            num_blue: 0,
+        };
+        for entry in hand.split(',') {
+            let Some(entry_capture) = re_entry.captures(entry) else {
+                break;
+            };
+            let count = entry_capture[1].parse::<u32>().unwrap();
+            match &entry_capture[2] {
+                "blue" => new_hand.num_blue = count,
+                "red" => new_hand.num_red = count,
+                "green" => new_hand.num_green = count,
+                _ => unreachable!(),
+            }
+        }
+        hands.push(new_hand);
+    }
+    RegexGame {
+        id: game_id.parse().unwrap(),
+        hands,
+    }
+}
+
+/// Times parsing every line of `log`, with `parse` returning the parsed game's id plus the
+/// number of cubes it drew, so that both parsers have to read every entry.
+fn time(name: &str, log: &[String], parse: impl Fn(&str) -> u64) -> Duration {
+    let start = Instant::now();
+    let checksum: u64 = log.iter().map(|line| parse(line)).sum();
+    let elapsed = start.elapsed();
+    println!("{name:>6}: {elapsed:?} (checksum {checksum})");
+    elapsed
+}
+
+fn main() {
+    let log = generate_log();
+    let regex = time("regex", &log, |line| {
+        let game = parse_with_regex(line);
+        let cubes = game
+            .hands
+            .iter()
+            .map(|hand| (hand.num_red + hand.num_green + hand.num_blue) as u64)
+            .sum::<u64>();
+        game.id as u64 + cubes
+    });
+    let bytes = time("bytes", &log, |line| {
+        let game = line.parse::<Game>().unwrap();
+        let cubes = game
+            .hands()
+            .iter()
+            .flat_map(Hand::counts)
+            .map(|(_, count)| count as u64)
+            .sum::<u64>();
+        game.id() as u64 + cubes
+    });
+    println!("speedup: {:.1}x", regex.as_secs_f64() / bytes.as_secs_f64());
+}
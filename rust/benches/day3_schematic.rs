@@ -0,0 +1,138 @@
+//! Times the day 3 solvers on generated schematics, comparing against the original layout of a
+//! `HashMap` entry per digit cell. Run with `cargo bench --bench day3_schematic`.
+
+use std::collections::HashMap;
+use std::io::{Seek, SeekFrom, Write};
+use std::time::{Duration, Instant};
+
+use rust::day3;
+
+/// A small xorshift generator so the schematics are the same on every run.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self, bound: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % bound
+    }
+}
+
+fn generate_schematic(size: usize) -> String {
+    let mut rng = XorShift(0x2023_1203);
+    let mut result = String::with_capacity((size + 1) * size);
+    for _ in 0..size {
+        let mut col = 0;
+        while col < size {
+            match rng.next(10) {
+                0..=5 => {
+                    result.push('.');
+                    col += 1;
+                }
+                6..=7 => {
+                    let width = (1 + rng.next(3) as usize).min(size - col);
+                    for _ in 0..width {
+                        result.push((b'0' + rng.next(10) as u8) as char);
+                    }
+                    col += width;
+                    if col < size {
+                        result.push('.');
+                        col += 1;
+                    }
+                }
+                8 => {
+                    result.push('*');
+                    col += 1;
+                }
+                _ => {
+                    result.push(['#', '$', '+', '-', '/', '@'][rng.next(6) as usize]);
+                    col += 1;
+                }
+            }
+        }
+        result.push('\n');
+    }
+    result
+}
+
+type Position = (isize, isize);
+
+/// The part 1 solver as it was before the dense grid: every digit cell holds a copy of its number.
+fn legacy_part_1(schematic: &str) -> u64 {
+    let mut map: HashMap<Position, (u64, Position, isize)> = HashMap::new();
+    let mut symbols: HashMap<(isize, isize), u8> = HashMap::new();
+    for (row, line) in schematic.lines().enumerate() {
+        let bytes = line.as_bytes();
+        let mut col = 0;
+        while col < bytes.len() {
+            if bytes[col].is_ascii_digit() {
+                let start = col;
+                while col < bytes.len() && bytes[col].is_ascii_digit() {
+                    col += 1;
+                }
+                let value = line[start..col].parse().unwrap();
+                for c in start..col {
+                    map.insert(
+                        (row as isize, c as isize),
+                        (value, (row as isize, start as isize), col as isize - 1),
+                    );
+                }
+            } else {
+                if bytes[col] != b'.' {
+                    symbols.insert((row as isize, col as isize), bytes[col]);
+                }
+                col += 1;
+            }
+        }
+    }
+    let mut total = 0;
+    for (&pos, &(value, first, last)) in map.iter() {
+        if pos != first {
+            continue;
+        }
+        let touches_symbol = (first.0 - 1..=first.0 + 1)
+            .any(|row| (first.1 - 1..=last + 1).any(|col| symbols.contains_key(&(row, col))));
+        if touches_symbol {
+            total += value;
+        }
+    }
+    total
+}
+
+fn time<T: std::fmt::Display>(name: &str, solve: impl FnOnce() -> T) -> Duration {
+    let start = Instant::now();
+    let answer = solve();
+    let elapsed = start.elapsed();
+    println!("{name:>24}: {elapsed:?} (answer {answer})");
+    elapsed
+}
+
+fn solve_file(schematic: &str, part: fn(&mut std::fs::File) -> u64) -> u64 {
+    let mut file = tempfile::tempfile().unwrap();
+    file.write_all(schematic.as_bytes()).unwrap();
+    file.seek(SeekFrom::Start(0)).unwrap();
+    part(&mut file)
+}
+
+fn part_1(file: &mut std::fs::File) -> u64 {
+    day3::run_part_1::<u64>(file).unwrap()
+}
+
+fn part_2(file: &mut std::fs::File) -> u64 {
+    day3::run_part_2::<u64>(file).unwrap()
+}
+
+fn main() {
+    let schematic = generate_schematic(2_000);
+    let legacy = time("legacy part 1, 2000²", || legacy_part_1(&schematic));
+    let dense = time("dense part 1, 2000²", || solve_file(&schematic, part_1));
+    println!(
+        "speedup: {:.1}x",
+        legacy.as_secs_f64() / dense.as_secs_f64()
+    );
+
+    let schematic = generate_schematic(10_000);
+    time("dense part 1, 10000²", || solve_file(&schematic, part_1));
+    time("dense part 2, 10000²", || solve_file(&schematic, part_2));
+}
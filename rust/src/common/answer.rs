@@ -0,0 +1,223 @@
+use std::error::Error;
+use std::fmt::{Debug, Display};
+
+/// An answer that no longer fits in the integer type it is being computed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverflowError {
+    pub type_name: &'static str,
+}
+
+impl Display for OverflowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "answer overflowed {}; compute it as a BigUint instead",
+            self.type_name
+        )
+    }
+}
+
+impl Error for OverflowError {}
+
+/// A number type the solvers can total their answers in.
+///
+/// Every operation is checked, so an input too large for the chosen type is reported as an
+/// [`OverflowError`] rather than silently wrapping.
+pub trait Answer: Sized + Clone + Debug + Display + PartialEq {
+    fn from_u64(value: u64) -> Self;
+
+    fn try_add(self, other: Self) -> Result<Self, OverflowError>;
+
+    fn try_mul(self, other: Self) -> Result<Self, OverflowError>;
+
+    fn try_sum(values: impl IntoIterator<Item = Self>) -> Result<Self, OverflowError> {
+        values
+            .into_iter()
+            .try_fold(Self::from_u64(0), |total, value| total.try_add(value))
+    }
+
+    fn try_product(values: impl IntoIterator<Item = Self>) -> Result<Self, OverflowError> {
+        values
+            .into_iter()
+            .try_fold(Self::from_u64(1), |total, value| total.try_mul(value))
+    }
+}
+
+impl Answer for u64 {
+    fn from_u64(value: u64) -> Self {
+        value
+    }
+
+    fn try_add(self, other: Self) -> Result<Self, OverflowError> {
+        self.checked_add(other)
+            .ok_or(OverflowError { type_name: "u64" })
+    }
+
+    fn try_mul(self, other: Self) -> Result<Self, OverflowError> {
+        self.checked_mul(other)
+            .ok_or(OverflowError { type_name: "u64" })
+    }
+}
+
+const LIMB_BASE: u64 = 1_000_000_000;
+
+/// An arbitrary precision unsigned integer, for answers to inputs too large for `u64`.
+///
+/// Stored as base 10⁹ limbs, least significant first, with no trailing zero limbs so that
+/// equal values compare equal.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    fn trim(mut self) -> Self {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+        self
+    }
+
+    /// `self - other`, or `None` if `other` is larger.
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        if other.limbs.len() > self.limbs.len() {
+            return None;
+        }
+        let mut limbs = Vec::with_capacity(self.limbs.len());
+        let mut borrow = 0;
+        for (i, &limb) in self.limbs.iter().enumerate() {
+            let subtrahend = *other.limbs.get(i).unwrap_or(&0) as u64 + borrow;
+            if limb as u64 >= subtrahend {
+                limbs.push((limb as u64 - subtrahend) as u32);
+                borrow = 0;
+            } else {
+                limbs.push((limb as u64 + LIMB_BASE - subtrahend) as u32);
+                borrow = 1;
+            }
+        }
+        (borrow == 0).then(|| Self { limbs }.trim())
+    }
+
+    /// The same value in another answer type, or an error if it does not fit.
+    pub fn to_answer<A: Answer>(&self) -> Result<A, OverflowError> {
+        self.limbs
+            .iter()
+            .rev()
+            .try_fold(A::from_u64(0), |total, &limb| {
+                total
+                    .try_mul(A::from_u64(LIMB_BASE))?
+                    .try_add(A::from_u64(limb as u64))
+            })
+    }
+}
+
+impl Answer for BigUint {
+    fn from_u64(mut value: u64) -> Self {
+        let mut limbs = vec![];
+        while value > 0 {
+            limbs.push((value % LIMB_BASE) as u32);
+            value /= LIMB_BASE;
+        }
+        Self { limbs }
+    }
+
+    fn try_add(self, other: Self) -> Result<Self, OverflowError> {
+        let mut limbs = Vec::with_capacity(self.limbs.len().max(other.limbs.len()) + 1);
+        let mut carry = 0;
+        for i in 0..self.limbs.len().max(other.limbs.len()) {
+            let sum = carry
+                + *self.limbs.get(i).unwrap_or(&0) as u64
+                + *other.limbs.get(i).unwrap_or(&0) as u64;
+            limbs.push((sum % LIMB_BASE) as u32);
+            carry = sum / LIMB_BASE;
+        }
+        limbs.push(carry as u32);
+        Ok(Self { limbs }.trim())
+    }
+
+    fn try_mul(self, other: Self) -> Result<Self, OverflowError> {
+        let mut limbs = vec![0u64; self.limbs.len() + other.limbs.len() + 1];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let product = limbs[i + j] + a as u64 * b as u64 + carry;
+                limbs[i + j] = product % LIMB_BASE;
+                carry = product / LIMB_BASE;
+            }
+            limbs[i + other.limbs.len()] += carry;
+        }
+        let limbs = limbs.into_iter().map(|limb| limb as u32).collect();
+        Ok(Self { limbs }.trim())
+    }
+}
+
+impl Display for BigUint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some((most_significant, rest)) = self.limbs.split_last() else {
+            return write!(f, "0");
+        };
+        write!(f, "{most_significant}")?;
+        for limb in rest.iter().rev() {
+            write!(f, "{limb:09}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Answer, BigUint, OverflowError};
+
+    #[test]
+    fn test_checked_u64() {
+        assert_eq!(u64::try_sum([1, 2, 3]), Ok(6));
+        assert_eq!(
+            u64::try_sum([u64::MAX, 1]),
+            Err(OverflowError { type_name: "u64" })
+        );
+        assert!(u64::try_product([1 << 32, 1 << 32]).is_err());
+    }
+
+    #[test]
+    fn test_big_uint() {
+        assert_eq!(BigUint::from_u64(0).to_string(), "0");
+        assert_eq!(
+            BigUint::from_u64(u64::MAX).to_string(),
+            u64::MAX.to_string()
+        );
+        let sum = BigUint::try_sum([BigUint::from_u64(u64::MAX), BigUint::from_u64(1)]).unwrap();
+        assert_eq!(sum.to_string(), "18446744073709551616");
+        let product =
+            BigUint::try_product([BigUint::from_u64(u64::MAX), BigUint::from_u64(u64::MAX)])
+                .unwrap();
+        assert_eq!(
+            product.to_string(),
+            "340282366920938463426481119284349108225"
+        );
+        assert_eq!(
+            BigUint::from_u64(1_000_000_000).try_mul(BigUint::from_u64(0)),
+            Ok(BigUint::from_u64(0))
+        );
+    }
+
+    #[test]
+    fn test_big_uint_sub_and_convert() {
+        let big = BigUint::try_sum([BigUint::from_u64(u64::MAX), BigUint::from_u64(2)]).unwrap();
+        assert_eq!(
+            big.to_answer::<u64>(),
+            Err(OverflowError { type_name: "u64" })
+        );
+        let back = big.checked_sub(&BigUint::from_u64(2)).unwrap();
+        assert_eq!(back.to_answer::<u64>(), Ok(u64::MAX));
+        assert_eq!(
+            BigUint::from_u64(1_000_000_000).checked_sub(&BigUint::from_u64(1)),
+            Some(BigUint::from_u64(999_999_999))
+        );
+        assert_eq!(big.checked_sub(&big), Some(BigUint::from_u64(0)));
+        assert_eq!(
+            BigUint::from_u64(1).checked_sub(&BigUint::from_u64(2)),
+            None
+        );
+        assert_eq!(BigUint::from_u64(0).to_answer::<u64>(), Ok(0));
+    }
+}
@@ -0,0 +1,355 @@
+use std::collections::HashSet;
+
+/// Which cells around an entry count as its neighbours, by their distance from the entry.
+///
+/// Distances are measured from the nearest cell of the entry, so a multi-cell entry such as a
+/// number is surrounded evenly along its whole length.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NeighbourhoodShape {
+    /// Within `radius` rows and `radius` columns, a square including the diagonals.
+    #[default]
+    Chebyshev,
+    /// Within `radius` steps up, down, left and right combined, a diamond.
+    Manhattan,
+    /// Within `radius` cells straight up, down, left or right, a cross with no diagonals at all.
+    VonNeumann,
+}
+
+/// A neighbourhood shape and size. The default is the eight cells touching a single cell, the
+/// Chebyshev neighbourhood of radius 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Neighbourhood {
+    pub shape: NeighbourhoodShape,
+    pub radius: usize,
+}
+
+impl Default for Neighbourhood {
+    fn default() -> Self {
+        Self {
+            shape: NeighbourhoodShape::Chebyshev,
+            radius: 1,
+        }
+    }
+}
+
+impl Neighbourhood {
+    pub fn new(shape: NeighbourhoodShape, radius: usize) -> Self {
+        Self { shape, radius }
+    }
+
+    /// Whether a cell `row_distance` rows and `col_distance` columns away from an entry is in the
+    /// neighbourhood. The entry's own cells, at no distance at all, are not.
+    pub fn contains(&self, row_distance: usize, col_distance: usize) -> bool {
+        if row_distance == 0 && col_distance == 0 {
+            return false;
+        }
+        match self.shape {
+            NeighbourhoodShape::Chebyshev => row_distance.max(col_distance) <= self.radius,
+            NeighbourhoodShape::Manhattan => row_distance + col_distance <= self.radius,
+            NeighbourhoodShape::VonNeumann => {
+                row_distance.min(col_distance) == 0 && row_distance.max(col_distance) <= self.radius
+            }
+        }
+    }
+
+    /// The neighbours of the rectangle of cells from `first` to `last` inclusive, as
+    /// `(row, col)` pairs in row-major order. Pass the same position twice for a single cell.
+    ///
+    /// Positions are not bounded; use [`NeighbourhoodIter::clipped`] to keep them on a grid.
+    pub fn around(&self, first: (isize, isize), last: (isize, isize)) -> NeighbourhoodIter {
+        let radius = self.radius as isize;
+        NeighbourhoodIter {
+            neighbourhood: *self,
+            first,
+            last,
+            top_left: (first.0 - radius, first.1 - radius),
+            bottom_right: (last.0 + radius, last.1 + radius),
+            cursor: (first.0 - radius, first.1 - radius),
+        }
+    }
+}
+
+/// What lies past the edges of a grid.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Edges {
+    /// Nothing: positions off the grid are dropped.
+    #[default]
+    Bounded,
+    /// The grid wraps around, so the column past the right edge is the leftmost column and the
+    /// row past the bottom is the top row.
+    Torus,
+    /// Each edge is a mirror: the column past the right edge is the rightmost column again, the
+    /// one past that the column before it, and likewise for rows and the other edges.
+    ///
+    /// A mirror only shows cells that are already in reach, so it adds no new cells, but what it
+    /// shows are images of them: [`NeighbourhoodIter::on_grid_images`] keeps each image apart, so
+    /// that an entry next to a mirror is next to another entry both directly and reflected.
+    Mirrored,
+}
+
+impl Edges {
+    /// Where `(row, col)` lands on a grid of `height` rows and `width` columns, or `None` if it is
+    /// off a bounded grid.
+    pub fn resolve(
+        &self,
+        (row, col): (isize, isize),
+        height: usize,
+        width: usize,
+    ) -> Option<(isize, isize)> {
+        let (height, width) = (height as isize, width as isize);
+        if height == 0 || width == 0 {
+            return None;
+        }
+        match self {
+            Edges::Bounded => {
+                ((0..height).contains(&row) && (0..width).contains(&col)).then_some((row, col))
+            }
+            Edges::Torus => Some((row.rem_euclid(height), col.rem_euclid(width))),
+            Edges::Mirrored => Some((reflect(row, height), reflect(col, width))),
+        }
+    }
+
+    /// Which copy of the grid `(row, col)` lies in, counting whole grids from the grid itself at
+    /// `(0, 0)`. Only mirrored edges tell the copies apart, as the copies of a torus are the grid
+    /// itself.
+    pub fn image(&self, (row, col): (isize, isize), height: usize, width: usize) -> (isize, isize) {
+        match self {
+            Edges::Mirrored if height > 0 && width > 0 => (
+                row.div_euclid(height as isize),
+                col.div_euclid(width as isize),
+            ),
+            _ => (0, 0),
+        }
+    }
+}
+
+/// Reflects `value` back and forth across the ends of `0..length` until it lands inside it.
+fn reflect(value: isize, length: isize) -> isize {
+    let folded = value.rem_euclid(2 * length);
+    if folded < length {
+        folded
+    } else {
+        2 * length - 1 - folded
+    }
+}
+
+/// Iterator over the cells of a [`Neighbourhood`], built by [`Neighbourhood::around`].
+#[derive(Clone, Debug)]
+pub struct NeighbourhoodIter {
+    neighbourhood: Neighbourhood,
+    first: (isize, isize),
+    last: (isize, isize),
+    top_left: (isize, isize),
+    bottom_right: (isize, isize),
+    cursor: (isize, isize),
+}
+
+/// How far `value` is outside `start..=end`.
+fn distance(value: isize, start: isize, end: isize) -> usize {
+    (start - value).max(value - end).max(0) as usize
+}
+
+impl NeighbourhoodIter {
+    /// Skips any position outside a grid of `height` rows and `width` columns.
+    pub fn clipped(mut self, height: usize, width: usize) -> Self {
+        self.top_left = (self.top_left.0.max(0), self.top_left.1.max(0));
+        self.bottom_right = (
+            self.bottom_right.0.min(height as isize - 1),
+            self.bottom_right.1.min(width as isize - 1),
+        );
+        self.cursor = self.top_left;
+        self
+    }
+
+    /// Moves every position onto a grid of `height` rows and `width` columns according to
+    /// `edges`. Bounded edges skip positions off the grid, as [`NeighbourhoodIter::clipped`]
+    /// does. Mirrored edges skip reflections that land on a cell already yielded or on the cells
+    /// the neighbourhood was built around, as those are only images of cells already in reach. A
+    /// torus may land on both.
+    pub fn on_grid(
+        self,
+        height: usize,
+        width: usize,
+        edges: Edges,
+    ) -> impl Iterator<Item = (isize, isize)> {
+        let (first, last) = (self.first, self.last);
+        let mut seen = HashSet::new();
+        self.on_grid_images(height, width, edges)
+            .map(|(cell, _)| cell)
+            .filter(move |&(row, col)| {
+                if edges != Edges::Mirrored {
+                    return true;
+                }
+                let inside = (first.0..=last.0).contains(&row) && (first.1..=last.1).contains(&col);
+                !inside && seen.insert((row, col))
+            })
+    }
+
+    /// Like [`NeighbourhoodIter::on_grid`], but yields each position with the copy of the grid it
+    /// lies in, from [`Edges::image`], and keeps every mirror image of a cell. No two items are
+    /// the same, and the cells the neighbourhood was built around are only ever yielded as images
+    /// in another copy.
+    pub fn on_grid_images(
+        self,
+        height: usize,
+        width: usize,
+        edges: Edges,
+    ) -> impl Iterator<Item = ((isize, isize), (isize, isize))> {
+        let positions = match edges {
+            Edges::Bounded => self.clipped(height, width),
+            Edges::Torus | Edges::Mirrored => self,
+        };
+        positions.filter_map(move |position| {
+            let cell = edges.resolve(position, height, width)?;
+            Some((cell, edges.image(position, height, width)))
+        })
+    }
+}
+
+impl Iterator for NeighbourhoodIter {
+    type Item = (isize, isize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.cursor.0 <= self.bottom_right.0 && self.top_left.1 <= self.bottom_right.1 {
+            let (row, col) = self.cursor;
+            if col < self.bottom_right.1 {
+                self.cursor.1 += 1;
+            } else {
+                self.cursor = (row + 1, self.top_left.1);
+            }
+            let row_distance = distance(row, self.first.0, self.last.0);
+            let col_distance = distance(col, self.first.1, self.last.1);
+            if self.neighbourhood.contains(row_distance, col_distance) {
+                return Some((row, col));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Edges, Neighbourhood, NeighbourhoodShape};
+
+    #[test]
+    fn test_shapes() {
+        let around = |shape, radius| {
+            Neighbourhood::new(shape, radius)
+                .around((0, 0), (0, 0))
+                .collect::<Vec<(isize, isize)>>()
+        };
+        assert_eq!(
+            around(NeighbourhoodShape::Chebyshev, 1),
+            vec![
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, -1),
+                (0, 1),
+                (1, -1),
+                (1, 0),
+                (1, 1)
+            ]
+        );
+        assert_eq!(around(NeighbourhoodShape::Chebyshev, 2).len(), 24);
+        assert_eq!(
+            around(NeighbourhoodShape::Manhattan, 1),
+            vec![(-1, 0), (0, -1), (0, 1), (1, 0)]
+        );
+        assert_eq!(around(NeighbourhoodShape::Manhattan, 2).len(), 12);
+        assert_eq!(
+            around(NeighbourhoodShape::VonNeumann, 2),
+            vec![
+                (-2, 0),
+                (-1, 0),
+                (0, -2),
+                (0, -1),
+                (0, 1),
+                (0, 2),
+                (1, 0),
+                (2, 0)
+            ]
+        );
+        assert!(around(NeighbourhoodShape::Chebyshev, 0).is_empty());
+    }
+
+    #[test]
+    fn test_span_and_clipping() {
+        let ring = Neighbourhood::default().around((1, 1), (1, 3));
+        assert_eq!(ring.clone().count(), 12);
+        assert_eq!(
+            ring.clipped(2, 4).collect::<Vec<(isize, isize)>>(),
+            vec![(0, 0), (0, 1), (0, 2), (0, 3), (1, 0)]
+        );
+        let diamond = Neighbourhood::new(NeighbourhoodShape::Manhattan, 2).around((0, 0), (0, 1));
+        assert_eq!(
+            diamond.clipped(3, 2).collect::<Vec<(isize, isize)>>(),
+            vec![(1, 0), (1, 1), (2, 0), (2, 1)]
+        );
+        assert_eq!(
+            Neighbourhood::default()
+                .around((0, 0), (0, 0))
+                .clipped(0, 0)
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_edges() {
+        let corner = |edges| {
+            Neighbourhood::default()
+                .around((0, 0), (0, 0))
+                .on_grid(3, 4, edges)
+                .collect::<Vec<(isize, isize)>>()
+        };
+        assert_eq!(corner(Edges::Bounded), vec![(0, 1), (1, 0), (1, 1)]);
+        assert_eq!(
+            corner(Edges::Torus),
+            vec![
+                (2, 3),
+                (2, 0),
+                (2, 1),
+                (0, 3),
+                (0, 1),
+                (1, 3),
+                (1, 0),
+                (1, 1)
+            ]
+        );
+        // Reflections of the corner onto itself, or onto cells already reached, are skipped.
+        assert_eq!(corner(Edges::Mirrored), vec![(0, 1), (1, 0), (1, 1)]);
+        // Kept apart, the images show the corner itself three times and two of its neighbours a
+        // second time, in the copies of the grid above and to the left.
+        assert_eq!(
+            Neighbourhood::default()
+                .around((0, 0), (0, 0))
+                .on_grid_images(3, 4, Edges::Mirrored)
+                .collect::<Vec<((isize, isize), (isize, isize))>>(),
+            vec![
+                ((0, 0), (-1, -1)),
+                ((0, 0), (-1, 0)),
+                ((0, 1), (-1, 0)),
+                ((0, 0), (0, -1)),
+                ((0, 1), (0, 0)),
+                ((1, 0), (0, -1)),
+                ((1, 0), (0, 0)),
+                ((1, 1), (0, 0))
+            ]
+        );
+        assert_eq!(Edges::Torus.image((-1, 4), 3, 4), (0, 0));
+        assert_eq!(Edges::Mirrored.image((-1, 4), 3, 4), (-1, 1));
+        assert_eq!(Edges::Torus.resolve((-5, 9), 3, 4), Some((1, 1)));
+        assert_eq!(Edges::Mirrored.resolve((-1, 4), 3, 4), Some((0, 3)));
+        assert_eq!(Edges::Mirrored.resolve((3, -2), 3, 4), Some((2, 1)));
+        assert_eq!(Edges::Mirrored.resolve((-5, 9), 3, 4), Some((1, 1)));
+        assert_eq!(Edges::Mirrored.resolve((6, 1), 3, 4), Some((0, 1)));
+        // On a grid one column wide every reflection lands back in that column.
+        let column = Neighbourhood::default()
+            .around((1, 0), (1, 0))
+            .on_grid(3, 1, Edges::Mirrored)
+            .collect::<Vec<(isize, isize)>>();
+        assert_eq!(column, vec![(0, 0), (2, 0)]);
+        assert_eq!(Edges::Torus.resolve((0, 0), 0, 0), None);
+    }
+}
@@ -0,0 +1,29 @@
+/// Quotes and escapes `value` as a JSON string literal.
+pub fn json_string(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Writes a finite number as-is and anything else, including a missing value, as `null`.
+pub fn json_number(value: Option<f64>) -> String {
+    match value {
+        Some(value) if value.is_finite() => value.to_string(),
+        _ => String::from("null"),
+    }
+}
+
+/// Writes each already-formatted value as an element of a JSON array.
+pub fn json_array<T: ToString>(values: impl Iterator<Item = T>) -> String {
+    let values = values.map(|v| v.to_string()).collect::<Vec<String>>();
+    format!("[{}]", values.join(","))
+}
@@ -1 +1,4 @@
+pub mod answer;
+pub mod grid;
+pub mod json;
 pub mod puzzle_input_reader;
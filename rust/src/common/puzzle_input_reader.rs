@@ -1,6 +1,6 @@
 use std::error::Error;
 use std::fs::File;
-use std::io::Read;
+use std::io::{self, Read};
 
 pub struct PuzzleInput<T>
 where
@@ -23,12 +23,51 @@ where
         file.read_to_string(&mut contents)?;
         let lines = contents
             .split("\n")
-            .filter(|s| s.len() > 0)
+            .filter(|s| !s.is_empty())
             .map(|s| s.to_string().into())
             .collect::<Vec<T>>();
 
         Ok(Self { lines })
     }
+
+    /// Reads every line of `file`, keeping the blank lines that [`PuzzleInput::from_file`] drops,
+    /// for inputs where a blank line is a row of its own. Only the empty line after a final
+    /// newline is left out.
+    pub fn rows_from_file(file: &mut File) -> io::Result<Self> {
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+        let contents = contents.strip_suffix("\n").unwrap_or(&contents);
+        let lines = if contents.is_empty() {
+            vec![]
+        } else {
+            contents.split("\n").map(|s| s.to_string().into()).collect()
+        };
+        Ok(Self { lines })
+    }
+
+    /// Reads `file` as sections of lines separated by blank lines, for inputs where the blank
+    /// lines that [`PuzzleInput::from_file`] drops are meaningful. Runs of blank lines count as a
+    /// single separator, so no section is empty.
+    pub fn sections_from_file(file: &mut File) -> Result<Vec<Self>, Box<dyn Error>> {
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+        let mut sections = vec![];
+        let mut lines = vec![];
+        for line in contents.split("\n") {
+            if line.is_empty() {
+                if !lines.is_empty() {
+                    sections.push(Self { lines });
+                    lines = vec![];
+                }
+            } else {
+                lines.push(line.to_string().into());
+            }
+        }
+        if !lines.is_empty() {
+            sections.push(Self { lines });
+        }
+        Ok(sections)
+    }
 }
 
 impl<'a, T> IntoIterator for &'a PuzzleInput<T>
@@ -96,6 +135,14 @@ pub mod test {
             MockFile { inner: tmp_file }
         }
 
+        /// A file holding raw bytes, for contents that are not valid UTF-8.
+        pub fn with_bytes(bytes: &[u8]) -> Self {
+            let mut tmp_file = tempfile::tempfile().unwrap();
+            tmp_file.write_all(bytes).unwrap();
+            tmp_file.seek(SeekFrom::Start(0)).unwrap();
+            MockFile { inner: tmp_file }
+        }
+
         pub fn get_file(&mut self) -> &mut File {
             &mut self.inner
         }
@@ -111,4 +158,29 @@ pub mod test {
             assert_eq!(*test_case.0, test_case.1);
         }
     }
+
+    #[test]
+    fn test_rows() {
+        let mut file = MockFile::with_contents("a\n\nb\n");
+        let input: PuzzleInput<String> = PuzzleInput::rows_from_file(&mut file.inner).unwrap();
+        assert_eq!(
+            input.into_iter().cloned().collect::<Vec<String>>(),
+            vec!["a", "", "b"]
+        );
+        let mut file = MockFile::with_contents("");
+        let input: PuzzleInput<String> = PuzzleInput::rows_from_file(&mut file.inner).unwrap();
+        assert_eq!(input.into_iter().count(), 0);
+    }
+
+    #[test]
+    fn test_sections() {
+        let mut file = MockFile::with_contents("a\nb\n\nc\n\n\n\nd\n");
+        let sections: Vec<PuzzleInput<String>> =
+            PuzzleInput::sections_from_file(&mut file.inner).unwrap();
+        let sections = sections
+            .iter()
+            .map(|section| section.into_iter().cloned().collect::<Vec<String>>())
+            .collect::<Vec<Vec<String>>>();
+        assert_eq!(sections, vec![vec!["a", "b"], vec!["c"], vec!["d"]]);
+    }
 }
@@ -1,3 +1,4 @@
+use crate::common::answer::{Answer, OverflowError};
 use crate::common::puzzle_input_reader::PuzzleInput;
 use std::fs::File;
 
@@ -9,9 +10,9 @@ impl From<String> for CalibrationValue {
     fn from(value: String) -> Self {
         let collection = value
             .as_bytes()
-            .to_owned()
-            .into_iter()
-            .filter(|&b| b >= b'0' && b <= b'9')
+            .iter()
+            .copied()
+            .filter(|b| b.is_ascii_digit())
             .collect::<Vec<u8>>();
         let new_value = String::from_utf8(vec![collection[0], collection[collection.len() - 1]])
             .unwrap()
@@ -67,10 +68,7 @@ const PART_2_SEARCH: [Search<'static>; 18] = [
     Search::Word("nine", "9"),
 ];
 
-fn find_first_last<'a, 'b>(
-    search_string: &'a str,
-    options: Vec<Search<'b>>,
-) -> (Search<'b>, Search<'b>) {
+fn find_first_last<'b>(search_string: &str, options: Vec<Search<'b>>) -> (Search<'b>, Search<'b>) {
     (
         options
             .iter()
@@ -79,7 +77,7 @@ fn find_first_last<'a, 'b>(
             .min_by(|x, y| x.0.unwrap().cmp(&y.0.unwrap()))
             .unwrap()
             .1
-            .clone(),
+            .to_owned(),
         options
             .iter()
             .map(|option| (search_string.rfind(option.value()), option))
@@ -87,7 +85,7 @@ fn find_first_last<'a, 'b>(
             .max_by(|x, y| x.0.unwrap().cmp(&y.0.unwrap()))
             .unwrap()
             .1
-            .clone(),
+            .to_owned(),
     )
 }
 
@@ -104,17 +102,17 @@ impl From<String> for UpdatedCalibrationValue {
     }
 }
 
-pub fn run_part_1(input: &mut File) -> u64 {
+pub fn run_part_1<A: Answer>(input: &mut File) -> Result<A, OverflowError> {
     let puzzle_input: PuzzleInput<CalibrationValue> = PuzzleInput::from_file(input).unwrap();
-    puzzle_input.into_iter().map(|c| c.value).sum()
+    A::try_sum(puzzle_input.into_iter().map(|c| A::from_u64(c.value)))
 }
 
-pub fn run_part_2(input: &mut File) -> u64 {
+pub fn run_part_2<A: Answer>(input: &mut File) -> Result<A, OverflowError> {
     let puzzle_input: PuzzleInput<UpdatedCalibrationValue> = PuzzleInput::from_file(input).unwrap();
     // for input in puzzle_input.into_iter() {
     //     println!("Value: {}", input.value)
     // }
-    puzzle_input.into_iter().map(|c| c.value).sum()
+    A::try_sum(puzzle_input.into_iter().map(|c| A::from_u64(c.value)))
 }
 
 #[cfg(test)]
@@ -124,7 +122,7 @@ mod test {
     #[test]
     fn test_simple_input_part_1() {
         let mut input = MockFile::with_contents("1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet");
-        let result = run_part_1(&mut input.get_file());
+        let result = run_part_1::<u64>(input.get_file()).unwrap();
         let expected_result = 142;
         assert_eq!(expected_result, result);
     }
@@ -132,7 +130,7 @@ mod test {
 
     fn test_simple_input_part_2() {
         let mut input = MockFile::with_contents("two1nine\neightwothree\nabcone2threexyz\nxtwone3four\n4nineeightseven2\nzoneight234\n7pqrstsixteen");
-        let result = run_part_2(&mut input.get_file());
+        let result = run_part_2::<u64>(input.get_file()).unwrap();
         let expected_result = 281;
         assert_eq!(expected_result, result);
     }
@@ -1,145 +1,1628 @@
-use core::panic;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt::Display;
 use std::fs::File;
+use std::io::{self, Read};
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
 
-use regex::Regex;
+use crate::common::answer::{Answer, OverflowError};
+use crate::common::json::{json_array, json_number, json_string};
 
-use crate::common::puzzle_input_reader::PuzzleInput;
+/// An interned cube colour name.
+///
+/// Colours order as `red`, `green` and `blue` and then every other colour by name, so hands
+/// are always written out in the same order whatever other colours have been seen.
+///
+/// Each distinct name is leaked the first time it is interned, so the memory held is bounded by
+/// the number of different colours seen rather than the number of records read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Colour(&'static str);
 
+const FIXED_COLOURS: [&str; 3] = ["red", "green", "blue"];
+
+fn colour_names() -> &'static Mutex<BTreeSet<&'static str>> {
+    static NAMES: OnceLock<Mutex<BTreeSet<&'static str>>> = OnceLock::new();
+    NAMES.get_or_init(|| Mutex::new(BTreeSet::from(FIXED_COLOURS)))
+}
+
+impl Colour {
+    pub const RED: Colour = Colour("red");
+    pub const GREEN: Colour = Colour("green");
+    pub const BLUE: Colour = Colour("blue");
+
+    pub fn intern(name: &str) -> Self {
+        let mut names = colour_names().lock().unwrap();
+        if let Some(&name) = names.get(name) {
+            return Colour(name);
+        }
+        let name = Box::leak(name.to_owned().into_boxed_str());
+        names.insert(name);
+        Colour(name)
+    }
+
+    pub fn name(self) -> &'static str {
+        self.0
+    }
+
+    /// The fixed colours rank by their position, and every other colour ranks after them.
+    fn rank(self) -> usize {
+        FIXED_COLOURS
+            .iter()
+            .position(|&name| name == self.0)
+            .unwrap_or(FIXED_COLOURS.len())
+    }
+}
+
+impl Ord for Colour {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.rank(), self.0).cmp(&(other.rank(), other.0))
+    }
+}
+
+impl PartialOrd for Colour {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for Colour {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The cubes drawn in one hand.
+///
+/// A hand only ever draws a few colours, so the counts are kept in a small vector sorted by
+/// colour rather than in a map.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
 pub struct Hand {
-    num_green: u32,
-    num_red: u32,
-    num_blue: u32,
+    counts: Vec<(Colour, u32)>,
 }
 
 impl Hand {
     pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn find(&self, colour: Colour) -> Result<usize, usize> {
+        self.counts.binary_search_by(|&(c, _)| c.cmp(&colour))
+    }
+
+    pub fn count(&self, colour: Colour) -> u32 {
+        self.find(colour).map_or(0, |index| self.counts[index].1)
+    }
+
+    pub fn set_count(&mut self, colour: Colour, count: u32) {
+        match self.find(colour) {
+            Ok(index) => self.counts[index].1 = count,
+            Err(index) => self.counts.insert(index, (colour, count)),
+        }
+    }
+
+    /// The colours drawn in this hand, with their counts, in colour order.
+    pub fn counts(&self) -> impl Iterator<Item = (Colour, u32)> + '_ {
+        self.counts.iter().copied()
+    }
+}
+
+/// The ways a game record can fail to parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseGameErrorKind {
+    /// The line does not start with `Game`.
+    MissingHeader,
+    ExpectedNumber,
+    NumberTooLarge,
+    ExpectedColour,
+    /// A specific separator was expected, such as the `:` after the game id.
+    ExpectedByte(u8),
+    /// The same colour was drawn twice in one hand, which only [`ParseMode::Lenient`] allows.
+    DuplicateColour(Colour),
+}
+
+/// A malformed game record, pointing at the 1-based line and column where parsing stopped.
+///
+/// Records parsed on their own are reported as line 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseGameError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseGameErrorKind,
+}
+
+impl Display for ParseGameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match self.kind {
+            ParseGameErrorKind::MissingHeader => write!(f, "expected `Game`"),
+            ParseGameErrorKind::ExpectedNumber => write!(f, "expected a number"),
+            ParseGameErrorKind::NumberTooLarge => write!(f, "number is too large"),
+            ParseGameErrorKind::ExpectedColour => write!(f, "expected a colour"),
+            ParseGameErrorKind::ExpectedByte(b) => write!(f, "expected `{}`", b as char),
+            ParseGameErrorKind::DuplicateColour(c) => write!(f, "{c} appears twice in one hand"),
+        }
+    }
+}
+
+impl Error for ParseGameError {}
+
+/// Anything that can stop a day 2 solver from producing an answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameLogError {
+    /// The game log could not be read.
+    Io(io::ErrorKind),
+    Parse(ParseGameError),
+    Overflow(OverflowError),
+}
+
+impl Display for GameLogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameLogError::Io(kind) => write!(f, "could not read the game log: {kind}"),
+            GameLogError::Parse(error) => write!(f, "{error}"),
+            GameLogError::Overflow(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for GameLogError {}
+
+impl From<io::Error> for GameLogError {
+    fn from(error: io::Error) -> Self {
+        GameLogError::Io(error.kind())
+    }
+}
+
+impl From<ParseGameError> for GameLogError {
+    fn from(error: ParseGameError) -> Self {
+        GameLogError::Parse(error)
+    }
+}
+
+impl From<OverflowError> for GameLogError {
+    fn from(error: OverflowError) -> Self {
+        GameLogError::Overflow(error)
+    }
+}
+
+/// How forgiving the parser is about entries within a hand.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Rejects a colour drawn twice in one hand and any entry that does not parse.
+    #[default]
+    Strict,
+    /// Adds up the counts of a colour drawn twice in one hand and skips entries that do not
+    /// parse, keeping the rest of the hand.
+    Lenient,
+}
+
+/// A cursor over the bytes of a single record.
+///
+/// Nothing is allocated while parsing apart from the hands themselves; colour names are
+/// matched in place and only interned the first time a new one is seen.
+struct GameParser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    line: usize,
+    mode: ParseMode,
+}
+
+impl<'a> GameParser<'a> {
+    fn new(value: &'a str, mode: ParseMode) -> Self {
         Self {
-            num_blue: 0,
-            num_green: 0,
-            num_red: 0,
+            bytes: value.as_bytes(),
+            pos: 0,
+            line: 1,
+            mode,
+        }
+    }
+
+    fn error(&self, kind: ParseGameErrorKind) -> ParseGameError {
+        self.error_at(self.pos, kind)
+    }
+
+    fn error_at(&self, pos: usize, kind: ParseGameErrorKind) -> ParseGameError {
+        ParseGameError {
+            line: self.line,
+            column: pos + 1,
+            kind,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos == self.bytes.len()
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek() == Some(b' ') {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), ParseGameError> {
+        if self.eat(byte) {
+            Ok(())
+        } else {
+            Err(self.error(ParseGameErrorKind::ExpectedByte(byte)))
+        }
+    }
+
+    fn header(&mut self) -> Result<(), ParseGameError> {
+        if self.bytes[self.pos..].starts_with(b"Game") {
+            self.pos += 4;
+            Ok(())
+        } else {
+            Err(self.error(ParseGameErrorKind::MissingHeader))
+        }
+    }
+
+    fn number(&mut self) -> Result<u32, ParseGameError> {
+        let start = self.pos;
+        let mut value: u32 = 0;
+        while let Some(digit @ b'0'..=b'9') = self.peek() {
+            value = value
+                .checked_mul(10)
+                .and_then(|v| v.checked_add((digit - b'0') as u32))
+                .ok_or_else(|| self.error_at(start, ParseGameErrorKind::NumberTooLarge))?;
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return Err(self.error(ParseGameErrorKind::ExpectedNumber));
+        }
+        Ok(value)
+    }
+
+    fn colour(&mut self) -> Result<Colour, ParseGameError> {
+        let start = self.pos;
+        while let Some(b'a'..=b'z') = self.peek() {
+            self.pos += 1;
+        }
+        match &self.bytes[start..self.pos] {
+            b"" => Err(self.error(ParseGameErrorKind::ExpectedColour)),
+            b"red" => Ok(Colour::RED),
+            b"green" => Ok(Colour::GREEN),
+            b"blue" => Ok(Colour::BLUE),
+            // Only ASCII letters were consumed, so this slice is valid UTF-8.
+            name => Ok(Colour::intern(std::str::from_utf8(name).unwrap())),
+        }
+    }
+
+    /// Parses a single `count colour` entry and adds it to `hand`.
+    fn entry(&mut self, hand: &mut Hand) -> Result<(), ParseGameError> {
+        self.skip_spaces();
+        let count = self.number()?;
+        if !self.eat(b' ') {
+            return Err(self.error(ParseGameErrorKind::ExpectedByte(b' ')));
+        }
+        self.skip_spaces();
+        let colour_pos = self.pos;
+        let colour = self.colour()?;
+        self.skip_spaces();
+        if !matches!(self.peek(), None | Some(b',') | Some(b';')) {
+            return Err(self.error(ParseGameErrorKind::ExpectedByte(b',')));
+        }
+        if self.mode == ParseMode::Strict && hand.find(colour).is_ok() {
+            return Err(self.error_at(colour_pos, ParseGameErrorKind::DuplicateColour(colour)));
+        }
+        let total = hand
+            .count(colour)
+            .checked_add(count)
+            .ok_or_else(|| self.error_at(colour_pos, ParseGameErrorKind::NumberTooLarge))?;
+        hand.set_count(colour, total);
+        Ok(())
+    }
+
+    /// Parses `a colour, b colour, ...` up to the next `;` or the end of the line. A hand with
+    /// no entries at all is allowed, since that is how an empty hand is written back out.
+    fn hand(&mut self) -> Result<Hand, ParseGameError> {
+        let mut hand = Hand::new();
+        self.skip_spaces();
+        if matches!(self.peek(), None | Some(b';')) {
+            return Ok(hand);
+        }
+        loop {
+            if let Err(error) = self.entry(&mut hand) {
+                if self.mode == ParseMode::Strict {
+                    return Err(error);
+                }
+                while !matches!(self.peek(), None | Some(b',') | Some(b';')) {
+                    self.pos += 1;
+                }
+            }
+            if !self.eat(b',') {
+                return Ok(hand);
+            }
+        }
+    }
+
+    fn game(&mut self) -> Result<Game, ParseGameError> {
+        self.header()?;
+        self.expect(b' ')?;
+        self.skip_spaces();
+        let id = self.number()? as usize;
+        self.expect(b':')?;
+        let mut hands = vec![self.hand()?];
+        while self.eat(b';') {
+            hands.push(self.hand()?);
+        }
+        self.finish()?;
+        Ok(Game { id, hands })
+    }
+
+    fn finish(&self) -> Result<(), ParseGameError> {
+        if self.at_end() {
+            Ok(())
+        } else {
+            Err(self.error(ParseGameErrorKind::ExpectedByte(b';')))
+        }
+    }
+}
+
+impl FromStr for Hand {
+    type Err = ParseGameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = GameParser::new(s, ParseMode::Strict);
+        let hand = parser.hand()?;
+        if parser.at_end() {
+            Ok(hand)
+        } else {
+            Err(parser.error(ParseGameErrorKind::ExpectedByte(b',')))
         }
     }
 }
 
+/// Writes a hand as `a colour, b colour, ...` in colour order.
+impl Display for Hand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, (colour, count)) in self.counts().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{count} {colour}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Hand {
+    /// Writes the hand as a JSON object from colour name to count.
+    pub fn to_json(&self) -> String {
+        let entries = self
+            .counts()
+            .map(|(colour, count)| format!("{}:{}", json_string(colour.name()), count))
+            .collect::<Vec<String>>();
+        format!("{{{}}}", entries.join(","))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Game {
     id: usize,
     hands: Vec<Hand>,
 }
 
+impl FromStr for Game {
+    type Err = ParseGameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, ParseMode::Strict)
+    }
+}
+
 impl From<String> for Game {
     fn from(value: String) -> Self {
-        let re_game_id = Regex::new(r"Game ([0-9]*)").unwrap();
-        let re_entry = Regex::new(r"([0-9]*) (blue|green|red)").unwrap();
-        let values = value.split(": ").collect::<Vec<&str>>();
-        let game_id: &str = &re_game_id.captures(values[0]).unwrap()[1];
-
-        let hands = values[1].split(";");
-
-        let mut result_hands: Vec<Hand> = Vec::new();
-
-        for hand in hands {
-            let mut new_hand = Hand::new();
-            for entry in hand.split(",") {
-                let Some(entry_capture) = re_entry.captures(entry) else {
-                    break;
-                };
-                match &entry_capture[2] {
-                    "blue" => new_hand.num_blue = entry_capture[1].parse::<u32>().unwrap(),
-                    "red" => new_hand.num_red = entry_capture[1].parse::<u32>().unwrap(),
-                    "green" => new_hand.num_green = entry_capture[1].parse::<u32>().unwrap(),
-                    _ => panic!("This should not hit"),
+        value
+            .parse()
+            .unwrap_or_else(|e| panic!("Invalid game record {value:?}: {e}"))
+    }
+}
+
+impl Game {
+    pub fn parse(value: &str, mode: ParseMode) -> Result<Self, ParseGameError> {
+        GameParser::new(value, mode).game()
+    }
+
+    pub fn new(id: usize, hands: Vec<Hand>) -> Self {
+        Self { id, hands }
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn hands(&self) -> &[Hand] {
+        &self.hands
+    }
+
+    /// Every colour count in every hand that does not fit in `bag`, in hand order.
+    pub fn violations(&self, bag: &Bag) -> Vec<LimitViolation> {
+        let mut result = vec![];
+        for (hand_index, hand) in self.hands.iter().enumerate() {
+            for (colour, count) in hand.counts() {
+                let limit = bag.limit(colour);
+                if count > limit {
+                    result.push(LimitViolation {
+                        hand_index,
+                        colour,
+                        limit,
+                        count,
+                    });
+                }
+            }
+        }
+        result
+    }
+
+    pub fn is_possible(&self, bag: &Bag) -> bool {
+        self.hands
+            .iter()
+            .all(|hand| hand.counts().all(|(c, count)| count <= bag.limit(c)))
+    }
+
+    /// Writes the game as `{"id":N,"hands":[...]}`, with each hand as in [`Hand::to_json`].
+    pub fn to_json(&self) -> String {
+        let hands = self
+            .hands
+            .iter()
+            .map(Hand::to_json)
+            .collect::<Vec<String>>();
+        format!("{{\"id\":{},\"hands\":[{}]}}", self.id, hands.join(","))
+    }
+
+    /// The smallest bag this game could have been played with.
+    pub fn minimum_bag(&self) -> Bag {
+        let mut bag = Bag::empty();
+        for hand in self.hands.iter() {
+            for (colour, count) in hand.counts() {
+                if count > bag.limit(colour) {
+                    bag.set_limit(colour, count);
                 }
             }
-            result_hands.push(new_hand);
         }
+        bag
+    }
+
+    /// Every colour that appears in any hand of this game.
+    pub fn colours(&self) -> impl Iterator<Item = Colour> + '_ {
+        self.hands
+            .iter()
+            .flat_map(|hand| hand.counts().map(|(colour, _)| colour))
+    }
+}
+
+/// Writes the game in the canonical `Game N: a colour, b colour; ...` form.
+///
+/// Parsing the output in [`ParseMode::Strict`] gives back an equal game, as long as the game
+/// has at least one hand.
+impl Display for Game {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Game {}:", self.id)?;
+        for (index, hand) in self.hands.iter().enumerate() {
+            if index > 0 {
+                write!(f, ";")?;
+            }
+            if !hand.counts.is_empty() {
+                write!(f, " {hand}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The output formats for a list of games.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GameFormat {
+    /// One canonical `Game N: ...` record per line.
+    #[default]
+    Text,
+    /// A JSON array of games.
+    Json,
+}
+
+pub fn write_games(
+    games: &[Game],
+    format: GameFormat,
+    output: &mut impl std::io::Write,
+) -> std::io::Result<()> {
+    match format {
+        GameFormat::Text => {
+            for game in games {
+                writeln!(output, "{game}")?;
+            }
+        }
+        GameFormat::Json => {
+            let games = games.iter().map(Game::to_json).collect::<Vec<String>>();
+            writeln!(output, "[{}]", games.join(","))?;
+        }
+    }
+    Ok(())
+}
 
+/// The number of cubes of each colour loaded into the bag.
+///
+/// A bag is written the same way as a single hand, e.g. `12 red, 13 green, 14 blue`,
+/// so it can be passed on the command line or stored in a file. Colours that are not
+/// listed have no cubes in the bag.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bag {
+    limits: BTreeMap<Colour, u32>,
+}
+
+impl Default for Bag {
+    fn default() -> Self {
+        Self::from_iter([(Colour::RED, 12), (Colour::GREEN, 13), (Colour::BLUE, 14)])
+    }
+}
+
+impl Bag {
+    pub fn empty() -> Self {
         Self {
-            id: String::from(game_id).parse::<usize>().unwrap(),
-            hands: result_hands,
+            limits: BTreeMap::new(),
         }
     }
+
+    pub fn from_file(file: &mut File) -> Result<Self, Box<dyn Error>> {
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+        Ok(contents.trim().parse()?)
+    }
+
+    pub fn limit(&self, colour: Colour) -> u32 {
+        self.limits.get(&colour).copied().unwrap_or(0)
+    }
+
+    pub fn set_limit(&mut self, colour: Colour, limit: u32) {
+        self.limits.insert(colour, limit);
+    }
+
+    /// The total number of cubes in the bag.
+    pub fn total(&self) -> u64 {
+        self.limits.values().map(|&limit| limit as u64).sum()
+    }
+
+    /// The product of the cube counts of `colours`, as used by part 2.
+    pub fn power<A: Answer>(
+        &self,
+        colours: impl IntoIterator<Item = Colour>,
+    ) -> Result<A, OverflowError> {
+        A::try_product(
+            colours
+                .into_iter()
+                .map(|colour| A::from_u64(self.limit(colour) as u64)),
+        )
+    }
 }
 
-const NUM_RED: u32 = 12;
-const NUM_GREEN: u32 = 13;
-const NUM_BLUE: u32 = 14;
+impl FromIterator<(Colour, u32)> for Bag {
+    fn from_iter<I: IntoIterator<Item = (Colour, u32)>>(iter: I) -> Self {
+        Self {
+            limits: iter.into_iter().collect(),
+        }
+    }
+}
 
-pub fn run_part_1(input: &mut File) -> usize {
-    let puzzle_input: PuzzleInput<Game> = PuzzleInput::from_file(input).unwrap();
-    puzzle_input
-        .into_iter()
-        .filter(|&game| {
-            game.hands.iter().all(|hand| {
-                hand.num_blue <= NUM_BLUE && hand.num_green <= NUM_GREEN && hand.num_red <= NUM_RED
-            })
-        })
-        .map(|game| game.id)
-        .sum()
+/// Writes the bag the same way it is parsed, as a hand.
+impl Display for Bag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hand = Hand {
+            counts: self
+                .limits
+                .iter()
+                .map(|(&colour, &limit)| (colour, limit))
+                .collect(),
+        };
+        write!(f, "{hand}")
+    }
 }
 
-pub fn run_part_2(input: &mut File) -> u32 {
-    let puzzle_input: PuzzleInput<Game> = PuzzleInput::from_file(input).unwrap();
-    puzzle_input
-        .into_iter()
-        .map(|game| {
-            let min_red = game
-                .hands
+impl FromStr for Bag {
+    type Err = ParseGameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(s.parse::<Hand>()?.counts().collect())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct LimitViolation {
+    pub hand_index: usize,
+    pub colour: Colour,
+    pub limit: u32,
+    pub count: u32,
+}
+
+impl LimitViolation {
+    pub fn excess(&self) -> u32 {
+        self.count - self.limit
+    }
+}
+
+impl Display for LimitViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "hand {}: {} {} exceeds limit of {} by {}",
+            self.hand_index,
+            self.count,
+            self.colour,
+            self.limit,
+            self.excess()
+        )
+    }
+}
+
+/// What a bag is scored on when comparing bag configurations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    /// The number of games that are possible with the bag.
+    PossibleGames,
+    /// The sum of the ids of the games that are possible with the bag, as in part 1.
+    IdSum,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoredBag {
+    pub bag: Bag,
+    pub score: usize,
+}
+
+/// A bag with limits for only the first few colours of a [`BagAnalysis`], and which games are
+/// still possible with it.
+struct PartialBag {
+    limits: Vec<u32>,
+    total: u64,
+    possible: Vec<bool>,
+}
+
+impl PartialBag {
+    /// Whether `self` uses no more cubes than `other` and allows every game that `other` does.
+    fn dominates(&self, other: &PartialBag) -> bool {
+        self.total <= other.total
+            && self
+                .possible
                 .iter()
-                .max_by(|&x, &y| x.num_red.cmp(&y.num_red))
-                .unwrap()
-                .num_red;
-            let min_green = game
-                .hands
+                .zip(other.possible.iter())
+                .all(|(&mine, &theirs)| mine || !theirs)
+    }
+}
+
+/// Answers questions about which bags the games could have been played with, using only the
+/// minimum bag of each game.
+pub struct BagAnalysis {
+    colours: Vec<Colour>,
+    minimum_bags: Vec<(usize, Bag)>,
+}
+
+impl BagAnalysis {
+    pub fn new(games: &[Game]) -> Self {
+        let colours = games
+            .iter()
+            .flat_map(Game::colours)
+            .collect::<BTreeSet<Colour>>();
+        Self {
+            colours: colours.into_iter().collect(),
+            minimum_bags: games
                 .iter()
-                .max_by(|&x, &y| x.num_green.cmp(&y.num_green))
-                .unwrap()
-                .num_green;
-            let min_blue = game
-                .hands
+                .map(|game| (game.id, game.minimum_bag()))
+                .collect(),
+        }
+    }
+
+    pub fn score(&self, bag: &Bag, objective: Objective) -> usize {
+        let possible = self.minimum_bags.iter().filter(|(_, minimum)| {
+            self.colours
+                .iter()
+                .all(|&colour| minimum.limit(colour) <= bag.limit(colour))
+        });
+        match objective {
+            Objective::PossibleGames => possible.count(),
+            Objective::IdSum => possible.map(|(id, _)| id).sum(),
+        }
+    }
+
+    /// Every bag that is not beaten by a bag with the same number of cubes or fewer, ordered by
+    /// the number of cubes.
+    ///
+    /// Only bags where each colour's count is exactly some game's minimum for that colour are
+    /// considered, since any other bag can give up cubes without losing a game. The bags are
+    /// built up one colour at a time, and a partial bag is dropped as soon as another uses no
+    /// more cubes and still allows every game it does, since whatever the remaining colours are
+    /// set to, the other bag does at least as well.
+    pub fn pareto_frontier(&self, objective: Objective) -> Vec<ScoredBag> {
+        let mut partials = vec![PartialBag {
+            limits: vec![],
+            total: 0,
+            possible: vec![true; self.minimum_bags.len()],
+        }];
+        for &colour in self.colours.iter() {
+            let mut values = self
+                .minimum_bags
+                .iter()
+                .map(|(_, minimum)| minimum.limit(colour))
+                .chain([0])
+                .collect::<Vec<u32>>();
+            values.sort_unstable();
+            values.dedup();
+
+            let mut extended = vec![];
+            for partial in partials.iter() {
+                for &value in values.iter() {
+                    let mut limits = partial.limits.clone();
+                    limits.push(value);
+                    extended.push(PartialBag {
+                        limits,
+                        total: partial.total + value as u64,
+                        possible: partial
+                            .possible
+                            .iter()
+                            .zip(self.minimum_bags.iter())
+                            .map(|(&possible, (_, minimum))| {
+                                possible && minimum.limit(colour) <= value
+                            })
+                            .collect(),
+                    });
+                }
+            }
+            partials = Self::undominated(extended);
+        }
+
+        let mut bags = partials
+            .into_iter()
+            .map(|partial| {
+                let bag = self.colours.iter().copied().zip(partial.limits).collect();
+                let score = self.score(&bag, objective);
+                ScoredBag { bag, score }
+            })
+            .collect::<Vec<ScoredBag>>();
+        bags.sort_by_key(|scored| (scored.bag.total(), std::cmp::Reverse(scored.score)));
+
+        let mut frontier: Vec<ScoredBag> = vec![];
+        for scored in bags {
+            if frontier.last().is_none_or(|best| scored.score > best.score) {
+                frontier.push(scored);
+            }
+        }
+        frontier
+    }
+
+    /// The partial bags that no other partial bag dominates, keeping the first of any that tie.
+    fn undominated(mut partials: Vec<PartialBag>) -> Vec<PartialBag> {
+        // Anything that could dominate a bag sorts before it.
+        partials.sort_by_key(|partial| {
+            let possible = partial
+                .possible
+                .iter()
+                .filter(|&&possible| possible)
+                .count();
+            (partial.total, std::cmp::Reverse(possible))
+        });
+        let mut kept: Vec<PartialBag> = vec![];
+        for partial in partials {
+            if !kept.iter().any(|other| other.dominates(&partial)) {
+                kept.push(partial);
+            }
+        }
+        kept
+    }
+
+    /// The bag holding exactly `budget` cubes that scores highest.
+    ///
+    /// Cubes that do not make any more games possible are put in the last colour seen. Returns
+    /// `None` if no games have been drawn, since there is nothing to split the budget between.
+    pub fn best_bag(&self, budget: u32, objective: Objective) -> Option<ScoredBag> {
+        let &last_colour = self.colours.last()?;
+        let mut best = self
+            .pareto_frontier(objective)
+            .into_iter()
+            .take_while(|scored| scored.bag.total() <= budget as u64)
+            .last()?;
+        let spare = budget - best.bag.total() as u32;
+        best.bag
+            .set_limit(last_colour, best.bag.limit(last_colour) + spare);
+        Some(best)
+    }
+}
+
+/// Summary figures for one colour across every hand of every game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColourStats {
+    pub colour: Colour,
+    /// The largest number of cubes of this colour drawn in a single hand.
+    pub max: u32,
+    /// The mean number drawn per hand, counting hands that drew none as zero.
+    pub mean: f64,
+    /// How many hands drew each number of cubes, for only the numbers that some hand drew.
+    pub histogram: BTreeMap<u32, usize>,
+}
+
+/// Summary statistics over a parsed game log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameStats {
+    pub num_games: usize,
+    pub num_hands: usize,
+    pub colours: Vec<ColourStats>,
+    /// How many games had each number of hands, indexed by number of hands.
+    pub hands_per_game: Vec<usize>,
+    /// The Pearson correlation between each pair of colours' counts over all hands, in the
+    /// same order as `colours`. `None` where a colour never varies.
+    pub correlation: Vec<Vec<Option<f64>>>,
+    /// The ids of the possible games that would become impossible if any colour in the bag
+    /// lost a single cube.
+    pub tight_games: Vec<usize>,
+}
+
+impl GameStats {
+    pub fn new(games: &[Game], bag: &Bag) -> Self {
+        let colours = games
+            .iter()
+            .flat_map(Game::colours)
+            .collect::<BTreeSet<Colour>>()
+            .into_iter()
+            .collect::<Vec<Colour>>();
+        let hands = games
+            .iter()
+            .flat_map(|game| game.hands.iter())
+            .collect::<Vec<&Hand>>();
+        let num_hands = hands.len();
+
+        let samples = colours
+            .iter()
+            .map(|&colour| {
+                hands
+                    .iter()
+                    .map(|hand| hand.count(colour) as f64)
+                    .collect::<Vec<f64>>()
+            })
+            .collect::<Vec<Vec<f64>>>();
+
+        let colour_stats = colours
+            .iter()
+            .zip(samples.iter())
+            .map(|(&colour, counts)| {
+                let max = hands
+                    .iter()
+                    .map(|hand| hand.count(colour))
+                    .max()
+                    .unwrap_or(0);
+                let mut histogram = BTreeMap::new();
+                for hand in hands.iter() {
+                    *histogram.entry(hand.count(colour)).or_insert(0) += 1;
+                }
+                ColourStats {
+                    colour,
+                    max,
+                    mean: mean(counts),
+                    histogram,
+                }
+            })
+            .collect();
+
+        let mut hands_per_game = vec![];
+        for game in games {
+            if hands_per_game.len() <= game.hands.len() {
+                hands_per_game.resize(game.hands.len() + 1, 0);
+            }
+            hands_per_game[game.hands.len()] += 1;
+        }
+
+        let correlation = samples
+            .iter()
+            .map(|x| samples.iter().map(|y| pearson(x, y)).collect())
+            .collect();
+
+        let tight_games = games
+            .iter()
+            .filter(|game| game.is_possible(bag))
+            .filter(|game| {
+                let minimum = game.minimum_bag();
+                // A colour the game needs none of cannot lose a cube, even from a bag with none.
+                game.colours().any(|colour| {
+                    let needed = minimum.limit(colour);
+                    needed > 0 && needed == bag.limit(colour)
+                })
+            })
+            .map(|game| game.id)
+            .collect();
+
+        Self {
+            num_games: games.len(),
+            num_hands,
+            colours: colour_stats,
+            hands_per_game,
+            correlation,
+            tight_games,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let colours = self
+            .colours
+            .iter()
+            .map(|stats| {
+                format!(
+                    "{{\"colour\":{},\"max\":{},\"mean\":{},\"histogram\":{}}}",
+                    json_string(stats.colour.name()),
+                    stats.max,
+                    json_number(Some(stats.mean)),
+                    json_histogram(&stats.histogram)
+                )
+            })
+            .collect::<Vec<String>>();
+        let correlation = self
+            .correlation
+            .iter()
+            .map(|row| json_array(row.iter().map(|&r| json_number(r))))
+            .collect::<Vec<String>>();
+        format!(
+            "{{\"games\":{},\"hands\":{},\"colours\":[{}],\"hands_per_game\":{},\"correlation\":[{}],\"tight_games\":{}}}",
+            self.num_games,
+            self.num_hands,
+            colours.join(","),
+            json_array(self.hands_per_game.iter()),
+            correlation.join(","),
+            json_array(self.tight_games.iter())
+        )
+    }
+}
+
+/// Writes the statistics as a set of plain text tables.
+impl Display for GameStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} games, {} hands", self.num_games, self.num_hands)?;
+        writeln!(f)?;
+        writeln!(f, "{:<8} {:>5} {:>8}  histogram", "colour", "max", "mean")?;
+        for stats in self.colours.iter() {
+            let histogram = stats
+                .histogram
                 .iter()
-                .max_by(|&x, &y| x.num_blue.cmp(&y.num_blue))
-                .unwrap()
-                .num_blue;
-            min_red * min_blue * min_green
+                .map(|(count, hands)| format!("{count}:{hands}"))
+                .collect::<Vec<String>>();
+            writeln!(
+                f,
+                "{:<8} {:>5} {:>8.3}  {}",
+                stats.colour.name(),
+                stats.max,
+                stats.mean,
+                histogram.join(" ")
+            )?;
+        }
+        writeln!(f)?;
+        write!(f, "{:<8}", "")?;
+        for stats in self.colours.iter() {
+            write!(f, " {:>8}", stats.colour.name())?;
+        }
+        writeln!(f)?;
+        for (stats, row) in self.colours.iter().zip(self.correlation.iter()) {
+            write!(f, "{:<8}", stats.colour.name())?;
+            for r in row {
+                match r {
+                    Some(r) => write!(f, " {r:>8.3}")?,
+                    None => write!(f, " {:>8}", "-")?,
+                }
+            }
+            writeln!(f)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "{:<8} {:>5}", "hands", "games")?;
+        for (num_hands, &num_games) in self.hands_per_game.iter().enumerate() {
+            if num_games > 0 {
+                writeln!(f, "{num_hands:<8} {num_games:>5}")?;
+            }
+        }
+        writeln!(f)?;
+        let tight_games = self
+            .tight_games
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<String>>();
+        writeln!(f, "tight games: {}", tight_games.join(", "))
+    }
+}
+
+/// Writes a histogram as a JSON object from each number of cubes to how many hands drew it.
+fn json_histogram(histogram: &BTreeMap<u32, usize>) -> String {
+    let entries = histogram
+        .iter()
+        .map(|(count, hands)| format!("{}:{}", json_string(&count.to_string()), hands))
+        .collect::<Vec<String>>();
+    format!("{{{}}}", entries.join(","))
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+fn pearson(x: &[f64], y: &[f64]) -> Option<f64> {
+    let (mean_x, mean_y) = (mean(x), mean(y));
+    let mut covariance = 0.0;
+    let mut variance_x = 0.0;
+    let mut variance_y = 0.0;
+    for (a, b) in x.iter().zip(y.iter()) {
+        covariance += (a - mean_x) * (b - mean_y);
+        variance_x += (a - mean_x) * (a - mean_x);
+        variance_y += (b - mean_y) * (b - mean_y);
+    }
+    if variance_x == 0.0 || variance_y == 0.0 {
+        None
+    } else {
+        Some(covariance / (variance_x * variance_y).sqrt())
+    }
+}
+
+pub struct ImpossibleGame {
+    pub id: usize,
+    pub violations: Vec<LimitViolation>,
+}
+
+/// Parses every record in a game log, skipping blank lines but counting them towards the line
+/// numbers reported in errors.
+pub fn parse_games(contents: &str, mode: ParseMode) -> Result<Vec<Game>, ParseGameError> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(index, line)| {
+            let mut parser = GameParser::new(line, mode);
+            parser.line = index + 1;
+            parser.game()
+        })
+        .collect()
+}
+
+pub fn read_games(input: &mut File, mode: ParseMode) -> Result<Vec<Game>, GameLogError> {
+    let mut contents = String::new();
+    input.read_to_string(&mut contents)?;
+    Ok(parse_games(&contents, mode)?)
+}
+
+pub fn run_part_1<A: Answer>(
+    input: &mut File,
+    bag: &Bag,
+    mode: ParseMode,
+) -> Result<A, GameLogError> {
+    let games = read_games(input, mode)?;
+    Ok(A::try_sum(
+        games
+            .iter()
+            .filter(|game| game.is_possible(bag))
+            .map(|game| A::from_u64(game.id as u64)),
+    )?)
+}
+
+/// Lists every game that could not have been played with `bag`, along with the reasons why.
+pub fn run_part_1_report(
+    input: &mut File,
+    bag: &Bag,
+    mode: ParseMode,
+) -> Result<Vec<ImpossibleGame>, GameLogError> {
+    let games = read_games(input, mode)?;
+    Ok(games
+        .iter()
+        .map(|game| ImpossibleGame {
+            id: game.id,
+            violations: game.violations(bag),
         })
-        .sum()
+        .filter(|report| !report.violations.is_empty())
+        .collect())
+}
+
+/// Sums the power of each game's minimum bag over every colour seen in the input, so a
+/// game that never draws one of those colours has a power of zero.
+pub fn run_part_2<A: Answer>(input: &mut File, mode: ParseMode) -> Result<A, GameLogError> {
+    let games = read_games(input, mode)?;
+    let colours = games
+        .iter()
+        .flat_map(Game::colours)
+        .collect::<BTreeSet<Colour>>();
+    Ok(games.iter().try_fold(A::from_u64(0), |total, game| {
+        total.try_add(game.minimum_bag().power(colours.iter().copied())?)
+    })?)
 }
 
 #[cfg(test)]
 mod test {
+    use std::collections::BTreeMap;
+
+    use crate::common::answer::{Answer, BigUint, OverflowError};
     use crate::common::puzzle_input_reader::test::MockFile;
 
-    use super::{run_part_1, run_part_2, Game};
+    use super::{
+        parse_games, run_part_1, run_part_1_report, run_part_2, write_games, Bag, BagAnalysis,
+        Colour, Game, GameFormat, GameLogError, GameStats, Hand, LimitViolation, Objective,
+        ParseGameError, ParseGameErrorKind, ParseMode,
+    };
 
     #[test]
     fn test_input_parsing() {
         let game = Game::from(String::from(
             "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
         ));
-        assert_eq!(game.hands[0].num_blue, 3);
-        assert_eq!(game.hands[0].num_red, 4);
-        assert_eq!(game.hands[0].num_green, 0);
-        assert_eq!(game.hands[1].num_blue, 6);
-        assert_eq!(game.hands[1].num_red, 1);
-        assert_eq!(game.hands[1].num_green, 2);
-        assert_eq!(game.hands[2].num_blue, 0);
-        assert_eq!(game.hands[2].num_red, 0);
-        assert_eq!(game.hands[2].num_green, 2);
+        assert_eq!(game.hands[0].count(Colour::BLUE), 3);
+        assert_eq!(game.hands[0].count(Colour::RED), 4);
+        assert_eq!(game.hands[0].count(Colour::GREEN), 0);
+        assert_eq!(game.hands[1].count(Colour::BLUE), 6);
+        assert_eq!(game.hands[1].count(Colour::RED), 1);
+        assert_eq!(game.hands[1].count(Colour::GREEN), 2);
+        assert_eq!(game.hands[2].count(Colour::BLUE), 0);
+        assert_eq!(game.hands[2].count(Colour::RED), 0);
+        assert_eq!(game.hands[2].count(Colour::GREEN), 2);
         assert_eq!(game.id, 1);
     }
 
+    #[test]
+    fn test_parse_errors() {
+        let cases = [
+            ("Gme 1: 3 blue", 1, ParseGameErrorKind::MissingHeader),
+            ("Game x: 3 blue", 6, ParseGameErrorKind::ExpectedNumber),
+            ("Game 1 3 blue", 7, ParseGameErrorKind::ExpectedByte(b':')),
+            (
+                "Game 1: 3 blue, red",
+                17,
+                ParseGameErrorKind::ExpectedNumber,
+            ),
+            (
+                "Game 1: 3 blue; 4",
+                18,
+                ParseGameErrorKind::ExpectedByte(b' '),
+            ),
+            ("Game 1: 3 blue, 4 ", 19, ParseGameErrorKind::ExpectedColour),
+            ("Game 1: 3 Blue", 11, ParseGameErrorKind::ExpectedColour),
+            (
+                "Game 1: 3 blue. 4 red",
+                15,
+                ParseGameErrorKind::ExpectedByte(b','),
+            ),
+            (
+                "Game 1: 9999999999 blue",
+                9,
+                ParseGameErrorKind::NumberTooLarge,
+            ),
+        ];
+        for (line, column, kind) in cases {
+            let error = line.parse::<Game>().err().unwrap();
+            let expected = ParseGameError {
+                line: 1,
+                column,
+                kind,
+            };
+            assert_eq!(error, expected, "{line}");
+        }
+    }
+
+    #[test]
+    fn test_strict_mode() {
+        let error = Game::parse("Game 1: 3 blue, 2 blue; 1 red", ParseMode::Strict)
+            .err()
+            .unwrap();
+        assert_eq!(error.column, 19);
+        assert_eq!(
+            error.kind,
+            ParseGameErrorKind::DuplicateColour(Colour::BLUE)
+        );
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 19: blue appears twice in one hand"
+        );
+
+        let error = parse_games(
+            "Game 1: 3 blue\n\nGame 3: 1 red, two green, 4 blue",
+            ParseMode::Strict,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(error.line, 3);
+        assert_eq!(error.column, 16);
+        assert_eq!(error.kind, ParseGameErrorKind::ExpectedNumber);
+    }
+
+    #[test]
+    fn test_lenient_mode() {
+        let game = Game::parse("Game 1: 3 blue, 2 blue; 1 red", ParseMode::Lenient).unwrap();
+        assert_eq!(game.hands[0].count(Colour::BLUE), 5);
+        assert_eq!(game.hands[1].count(Colour::RED), 1);
+
+        let game = Game::parse(
+            "Game 3: 1 red, two green, 4 blue; 2 Green; 5 green",
+            ParseMode::Lenient,
+        )
+        .unwrap();
+        assert_eq!(game.hands.len(), 3);
+        assert_eq!(game.hands[0].count(Colour::RED), 1);
+        assert_eq!(game.hands[0].count(Colour::GREEN), 0);
+        assert_eq!(game.hands[0].count(Colour::BLUE), 4);
+        assert_eq!(game.hands[1], Hand::new());
+        assert_eq!(game.hands[2].count(Colour::GREEN), 5);
+
+        let games = parse_games(
+            "Game 1: 3 blue, 2 blue\nGame 2: 1 red, two green",
+            ParseMode::Lenient,
+        )
+        .unwrap();
+        assert_eq!(games.len(), 2);
+
+        let error = Game::parse("Gme 1: 3 blue", ParseMode::Lenient)
+            .err()
+            .unwrap();
+        assert_eq!(error.kind, ParseGameErrorKind::MissingHeader);
+    }
+
+    #[test]
+    fn test_solver_parse_modes() {
+        let contents = "Game 1: 3 blue, 2 blue\nGame 2: 1 red, two green";
+        let mut input = MockFile::with_contents(contents);
+        assert_eq!(
+            run_part_1::<u64>(input.get_file(), &Bag::default(), ParseMode::Strict),
+            Err(GameLogError::Parse(ParseGameError {
+                line: 1,
+                column: 19,
+                kind: ParseGameErrorKind::DuplicateColour(Colour::BLUE)
+            }))
+        );
+        let mut input = MockFile::with_contents(contents);
+        assert_eq!(
+            run_part_1::<u64>(input.get_file(), &Bag::default(), ParseMode::Lenient),
+            Ok(3)
+        );
+        let mut input = MockFile::with_contents(contents);
+        assert_eq!(
+            run_part_2::<u64>(input.get_file(), ParseMode::Lenient),
+            Ok(0)
+        );
+        let mut input = MockFile::with_contents("Game 1 3 blue");
+        assert!(matches!(
+            run_part_2::<u64>(input.get_file(), ParseMode::Lenient),
+            Err(GameLogError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let lines = [
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+            "Game 12: 0 red;  7 yellow ,2 red;;",
+            "Game 3: 2 blue, 2 blue",
+        ];
+        for line in lines {
+            let game = Game::parse(line, ParseMode::Lenient).unwrap();
+            let text = game.to_string();
+            assert_eq!(text.parse::<Game>().unwrap(), game, "{text}");
+            assert_eq!(text.parse::<Game>().unwrap().to_string(), text);
+        }
+        let game: Game = lines[0].parse().unwrap();
+        assert_eq!(
+            game.to_string(),
+            "Game 1: 4 red, 3 blue; 1 red, 2 green, 6 blue; 2 green"
+        );
+        let game = Game::parse(lines[1], ParseMode::Lenient).unwrap();
+        assert_eq!(game.to_string(), "Game 12: 0 red; 2 red, 7 yellow;;");
+    }
+
+    #[test]
+    fn test_json() {
+        let game: Game = "Game 1: 3 blue, 4 red; ; 2 green".parse().unwrap();
+        assert_eq!(
+            game.to_json(),
+            r#"{"id":1,"hands":[{"red":4,"blue":3},{},{"green":2}]}"#
+        );
+    }
+
+    #[test]
+    fn test_write_possible_games() {
+        let contents = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\nGame 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green\nGame 5: 6 red, 1 blue, 3 green";
+        let games = parse_games(contents, ParseMode::Strict)
+            .unwrap()
+            .into_iter()
+            .filter(|game| game.is_possible(&Bag::default()))
+            .collect::<Vec<Game>>();
+
+        let mut text = vec![];
+        write_games(&games, GameFormat::Text, &mut text).unwrap();
+        let text = String::from_utf8(text).unwrap();
+        assert_eq!(
+            text,
+            "Game 1: 4 red, 3 blue; 1 red, 2 green, 6 blue; 2 green\nGame 5: 6 red, 3 green, 1 blue\n"
+        );
+        assert_eq!(parse_games(&text, ParseMode::Strict).unwrap(), games);
+
+        let mut json = vec![];
+        write_games(&games[1..], GameFormat::Json, &mut json).unwrap();
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            "[{\"id\":5,\"hands\":[{\"red\":6,\"green\":3,\"blue\":1}]}]\n"
+        );
+    }
+
+    #[test]
+    fn test_best_bag() {
+        let contents = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\nGame 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue\nGame 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red\nGame 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red\nGame 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+        let games = parse_games(contents, ParseMode::Strict).unwrap();
+        let analysis = BagAnalysis::new(&games);
+
+        for objective in [Objective::PossibleGames, Objective::IdSum] {
+            for budget in [0, 5, 10, 15, 20, 39, 60] {
+                let mut expected = 0;
+                for red in 0..=budget {
+                    for green in 0..=budget - red {
+                        let bag = Bag::from_iter([
+                            (Colour::RED, red),
+                            (Colour::GREEN, green),
+                            (Colour::BLUE, budget - red - green),
+                        ]);
+                        expected = expected.max(analysis.score(&bag, objective));
+                    }
+                }
+                let best = analysis.best_bag(budget, objective).unwrap();
+                assert_eq!(best.bag.total(), budget as u64);
+                assert_eq!(best.score, expected, "{objective:?} {budget}");
+                assert_eq!(analysis.score(&best.bag, objective), expected);
+            }
+        }
+
+        let best = analysis.best_bag(15, Objective::IdSum).unwrap();
+        assert_eq!(best.score, 1 + 2 + 5);
+        assert_eq!(best.bag, "6 red, 3 green, 6 blue".parse().unwrap());
+    }
+
+    #[test]
+    fn test_pareto_frontier() {
+        let contents = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\nGame 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue\nGame 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red\nGame 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red\nGame 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+        let games = parse_games(contents, ParseMode::Strict).unwrap();
+        let frontier = BagAnalysis::new(&games).pareto_frontier(Objective::PossibleGames);
+        let points = frontier
+            .iter()
+            .map(|scored| (scored.bag.total(), scored.score))
+            .collect::<Vec<(u64, usize)>>();
+        assert_eq!(
+            points,
+            vec![(0, 0), (8, 1), (13, 2), (15, 3), (32, 4), (48, 5)]
+        );
+        assert_eq!(frontier[1].bag, "1 red, 3 green, 4 blue".parse().unwrap());
+        assert_eq!(frontier[4].bag, "14 red, 3 green, 15 blue".parse().unwrap());
+        assert_eq!(
+            frontier[5].bag,
+            "20 red, 13 green, 15 blue".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_pareto_frontier_with_many_colours() {
+        // Every game draws the same number of cubes of each of 30 colours, so there are 11^30
+        // bags to choose from but only 11 worth keeping after each colour.
+        let colours = (0..30u8)
+            .map(|c| format!("c{}", (b'a' + c % 26) as char).repeat(c as usize / 26 + 1))
+            .collect::<Vec<String>>();
+        let contents = (1..=10)
+            .map(|n| {
+                let hand = colours
+                    .iter()
+                    .map(|colour| format!("{n} {colour}"))
+                    .collect::<Vec<String>>();
+                format!("Game {n}: {}", hand.join(", "))
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        let games = parse_games(&contents, ParseMode::Strict).unwrap();
+        let frontier = BagAnalysis::new(&games).pareto_frontier(Objective::PossibleGames);
+        let points = frontier
+            .iter()
+            .map(|scored| (scored.bag.total(), scored.score))
+            .collect::<Vec<(u64, usize)>>();
+        assert_eq!(
+            points,
+            (0..=10).map(|n| (30 * n as u64, n)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        let contents = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\nGame 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue\nGame 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red\nGame 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red\nGame 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+        let games = parse_games(contents, ParseMode::Strict).unwrap();
+        let stats = GameStats::new(&games, &"6 red, 3 green, 6 blue".parse().unwrap());
+
+        assert_eq!(stats.num_games, 5);
+        assert_eq!(stats.num_hands, 14);
+        assert_eq!(stats.hands_per_game, vec![0, 0, 1, 4]);
+        assert_eq!(stats.colours.len(), 3);
+        let red = &stats.colours[0];
+        assert_eq!(red.colour, Colour::RED);
+        assert_eq!(red.max, 20);
+        assert!((red.mean - 61.0 / 14.0).abs() < 1e-9);
+        assert_eq!(
+            red.histogram,
+            BTreeMap::from([(0, 3), (1, 4), (3, 1), (4, 2), (6, 2), (14, 1), (20, 1)])
+        );
+        for (i, row) in stats.correlation.iter().enumerate() {
+            assert!((row[i].unwrap() - 1.0).abs() < 1e-9);
+            for (j, r) in row.iter().enumerate() {
+                assert_eq!(*r, stats.correlation[j][i]);
+            }
+        }
+        assert_eq!(stats.tight_games, vec![1, 2, 5]);
+        assert!(GameStats::new(&games, &Bag::default())
+            .tight_games
+            .is_empty());
+
+        // Games 2 and 3 drew no green cubes, so a bag with none does not make them tight.
+        let games = parse_games(
+            "Game 1: 2 red, 0 green\nGame 2: 1 red, 0 green\nGame 3: 0 green",
+            ParseMode::Strict,
+        )
+        .unwrap();
+        let bag = "2 red, 0 green".parse().unwrap();
+        assert_eq!(GameStats::new(&games, &bag).tight_games, vec![1]);
+
+        let json = stats.to_json();
+        assert!(json.starts_with(r#"{"games":5,"hands":14,"colours":[{"colour":"red","max":20,"#));
+        assert!(json.contains(r#""histogram":{"0":3,"1":4,"3":1,"4":2,"6":2,"14":1,"20":1}"#));
+        assert!(json.ends_with(r#""tight_games":[1,2,5]}"#));
+        let table = stats.to_string();
+        assert!(table.starts_with("5 games, 14 hands\n"));
+        assert!(table.ends_with("tight games: 1, 2, 5\n"));
+    }
+
+    #[test]
+    fn test_stats_large_counts() {
+        let games = parse_games("Game 1: 4000000000 red; 2 red", ParseMode::Strict).unwrap();
+        let stats = GameStats::new(&games, &Bag::default());
+        assert_eq!(
+            stats.colours[0].histogram,
+            BTreeMap::from([(2, 1), (4000000000, 1)])
+        );
+        assert!(stats.to_string().contains("  2:1 4000000000:1\n"));
+    }
+
+    #[test]
+    fn test_stats_constant_colour() {
+        let games = parse_games(
+            "Game 1: 2 red, 1 blue\nGame 2: 2 red, 3 blue",
+            ParseMode::Strict,
+        )
+        .unwrap();
+        let stats = GameStats::new(&games, &Bag::default());
+        assert_eq!(stats.correlation[0], vec![None, None]);
+        assert_eq!(stats.correlation[1][1], Some(1.0));
+        assert!(stats
+            .to_json()
+            .contains(r#""correlation":[[null,null],[null,1]]"#));
+    }
+
+    #[test]
+    fn test_other_colours() {
+        let game = Game::from(String::from("Game 7: 3 yellow, 4 red; 2 green, 6 yellow"));
+        let yellow = Colour::intern("yellow");
+        assert_eq!(yellow.name(), "yellow");
+        assert_eq!(game.hands[0].count(yellow), 3);
+        assert_eq!(game.hands[0].count(Colour::RED), 4);
+        assert_eq!(game.hands[1].count(yellow), 6);
+        assert!(!game.is_possible(&Bag::default()));
+        assert!(game.is_possible(&"4 red, 2 green, 6 yellow".parse::<Bag>().unwrap()));
+        assert_eq!(
+            game.minimum_bag()
+                .power::<u64>([Colour::RED, Colour::GREEN, yellow]),
+            Ok(48)
+        );
+    }
+
+    #[test]
+    fn test_colour_order() {
+        // Interned out of name order, but still written out by name after the fixed colours.
+        let zinc = Colour::intern("zinc");
+        let amber = Colour::intern("amber");
+        assert!(Colour::RED < Colour::GREEN && Colour::GREEN < Colour::BLUE);
+        assert!(Colour::BLUE < amber && amber < zinc);
+        let hand = "1 zinc, 2 amber, 3 blue, 4 red".parse::<Hand>().unwrap();
+        assert_eq!(hand.to_string(), "4 red, 3 blue, 2 amber, 1 zinc");
+        assert_eq!(Colour::intern("zinc").name(), zinc.name());
+        assert_eq!(Colour::intern("red"), Colour::RED);
+    }
+
+    #[test]
+    fn test_sample_input_other_colours() {
+        let mut sample_input = MockFile::with_contents("Game 1: 3 blue, 4 yellow; 1 yellow, 2 green\nGame 2: 1 blue, 2 green, 5 yellow\nGame 3: 8 green, 6 blue");
+        let bag = "4 yellow, 13 green, 14 blue".parse::<Bag>().unwrap();
+        assert_eq!(
+            run_part_1::<u64>(sample_input.get_file(), &bag, ParseMode::Strict),
+            Ok(4)
+        );
+        let mut sample_input = MockFile::with_contents("Game 1: 3 blue, 4 yellow; 1 yellow, 2 green\nGame 2: 1 blue, 2 green, 5 yellow\nGame 3: 8 green, 6 blue");
+        assert_eq!(
+            run_part_2::<u64>(sample_input.get_file(), ParseMode::Strict),
+            Ok(3 * 4 * 2 + 2 * 5)
+        );
+    }
+
     #[test]
     fn test_sample_input_1() {
         let mut sample_input = MockFile::with_contents("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\nGame 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue\nGame 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red\nGame 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red\nGame 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green");
-        let actual_result = run_part_1(sample_input.get_file());
+        let actual_result =
+            run_part_1::<u64>(sample_input.get_file(), &Bag::default(), ParseMode::Strict).unwrap();
         let expected_result = 8;
         assert_eq!(actual_result, expected_result);
     }
 
+    #[test]
+    fn test_custom_bag() {
+        let mut sample_input = MockFile::with_contents("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\nGame 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue\nGame 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red\nGame 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red\nGame 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green");
+        let bag = "20 red, 13 green, 15 blue".parse::<Bag>().unwrap();
+        assert_eq!(bag.limit(Colour::RED), 20);
+        assert_eq!(bag.limit(Colour::BLUE), 15);
+        let actual_result =
+            run_part_1::<u64>(sample_input.get_file(), &bag, ParseMode::Strict).unwrap();
+        let expected_result = 15;
+        assert_eq!(actual_result, expected_result);
+    }
+
+    #[test]
+    fn test_violation_report() {
+        let mut sample_input = MockFile::with_contents("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\nGame 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue\nGame 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red\nGame 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red\nGame 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green");
+        let report =
+            run_part_1_report(sample_input.get_file(), &Bag::default(), ParseMode::Strict).unwrap();
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0].id, 3);
+        assert_eq!(
+            report[0].violations,
+            vec![LimitViolation {
+                hand_index: 0,
+                colour: Colour::RED,
+                limit: 12,
+                count: 20
+            }]
+        );
+        assert_eq!(report[1].id, 4);
+        assert_eq!(report[1].violations.len(), 2);
+        assert_eq!(report[1].violations[0].colour, Colour::RED);
+        assert_eq!(report[1].violations[0].excess(), 2);
+        assert_eq!(report[1].violations[1].colour, Colour::BLUE);
+        assert_eq!(report[1].violations[1].excess(), 1);
+        assert_eq!(
+            report[1].violations[1].to_string(),
+            "hand 2: 15 blue exceeds limit of 14 by 1"
+        );
+    }
+
+    #[test]
+    fn test_overflow() {
+        let contents = "Game 1: 4000000000 red, 4000000000 green, 4000000000 blue\nGame 2: 4000000000 red, 4000000000 green, 4000000000 blue";
+        let mut sample_input = MockFile::with_contents(contents);
+        let error = run_part_2::<u64>(sample_input.get_file(), ParseMode::Strict).unwrap_err();
+        assert_eq!(
+            error,
+            GameLogError::Overflow(OverflowError { type_name: "u64" })
+        );
+        let mut sample_input = MockFile::with_contents(contents);
+        let result = run_part_2::<BigUint>(sample_input.get_file(), ParseMode::Strict).unwrap();
+        assert_eq!(result.to_string(), "128000000000000000000000000000");
+        let mut sample_input = MockFile::with_contents(contents);
+        let result =
+            run_part_1::<BigUint>(sample_input.get_file(), &Bag::default(), ParseMode::Strict)
+                .unwrap();
+        assert_eq!(result, BigUint::from_u64(0));
+    }
+
     #[test]
     fn test_sample_input_2() {
         let mut sample_input = MockFile::with_contents("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\nGame 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue\nGame 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red\nGame 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red\nGame 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green");
-        let actual_result = run_part_2(sample_input.get_file());
+        let actual_result = run_part_2::<u64>(sample_input.get_file(), ParseMode::Strict).unwrap();
         let expected_result = 2286;
         assert_eq!(actual_result, expected_result);
     }
@@ -1,86 +1,298 @@
-use std::{collections::{HashMap, hash_set, HashSet}, fs::File};
+use std::error::Error;
+use std::fmt::Display;
+use std::fs::File;
+use std::io;
 
+use crate::common::answer::{Answer, OverflowError};
+use crate::common::grid::{Edges, Neighbourhood};
 use crate::common::puzzle_input_reader::PuzzleInput;
 
-enum SchematicEntryBuilder {
-    Number { value: u32, width: u32 },
-    BlankSpace { width: u32 },
-    Symbol,
-    Gear,
+mod clusters;
+mod editor;
+mod graph;
+mod query;
+mod render;
+mod streaming;
+mod tokens;
+
+pub use clusters::{BoundingBox, Cluster};
+pub use editor::{EditSchematicError, SchematicEditor};
+pub use graph::{build_graph, AdjacencyGraph, NumberNode, SymbolNode};
+pub use query::{Gear, SchematicItem};
+pub use render::{render_schematic, RenderOptions, RenderWindow};
+pub use streaming::{run_streaming, SchematicEvent, SchematicStream};
+pub use tokens::{run_tokenized, Decimal, TokenConfig};
+
+use tokens::Token;
+
+enum SchematicEntryBuilder<V = u64> {
+    Number { value: V, width: u32 },
+    BlankSpace { width: u32, character: char },
+    Symbol(char),
+    Gear(char),
+}
+
+struct SchematicEntryBuilderLine<V = u64> {
+    line: Vec<SchematicEntryBuilder<V>>,
+}
+
+/// What the numbers of a schematic are parsed into: `u64` for every solver but
+/// [`run_tokenized`], which needs [`Decimal`] for signed and fractional numbers.
+trait NumberValue: Copy {
+    fn parse_number(text: &str) -> Result<Self, ParseSchematicErrorKind>;
+}
+
+impl NumberValue for u64 {
+    fn parse_number(text: &str) -> Result<Self, ParseSchematicErrorKind> {
+        if !text.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseSchematicErrorKind::UnsupportedNumber(text.to_string()));
+        }
+        text.parse().map_err(|_| ParseSchematicErrorKind::NumberTooLarge(text.to_string()))
+    }
+}
+
+/// How many numbers a gear has to touch for it to have a ratio.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GearAdjacency {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+impl GearAdjacency {
+    pub fn matches(&self, num_numbers: usize) -> bool {
+        match *self {
+            GearAdjacency::Exactly(n) => num_numbers == n,
+            GearAdjacency::AtLeast(n) => num_numbers >= n,
+        }
+    }
+
+    /// Past this many numbers a gear can no longer match, so the search can stop early.
+    fn limit(&self) -> Option<usize> {
+        match *self {
+            GearAdjacency::Exactly(n) => Some(n),
+            GearAdjacency::AtLeast(_) => None,
+        }
+    }
+}
+
+/// Which characters of a schematic mean what, and what makes a gear.
+///
+/// Digits are always part of a number. Otherwise a character is blank if it is in `blanks`, a
+/// gear if it is in `gears`, and a symbol if it is in `symbols`, or if `symbols` is `None` and it
+/// is anything else. A gear also counts as a symbol for part 1.
+///
+/// `neighbourhood` decides which cells count as next to a number or gear, `edges` whether that
+/// reaches past the edges of the schematic, and `ragged_rows` what happens to rows of different
+/// widths.
+///
+/// With `tokens` set, the schematic is split into the tokens of a [`TokenConfig`] instead, so
+/// that signed numbers, decimals and multi-character operators each count as one entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchematicConfig {
+    pub blanks: String,
+    pub symbols: Option<String>,
+    pub gears: String,
+    pub gear_adjacency: GearAdjacency,
+    pub neighbourhood: Neighbourhood,
+    pub edges: Edges,
+    pub ragged_rows: RaggedRows,
+    pub tokens: Option<TokenConfig>,
+}
+
+impl Default for SchematicConfig {
+    fn default() -> Self {
+        Self {
+            blanks: String::from("."),
+            symbols: None,
+            gears: String::from("*"),
+            gear_adjacency: GearAdjacency::Exactly(2),
+            neighbourhood: Neighbourhood::default(),
+            edges: Edges::default(),
+            ragged_rows: RaggedRows::default(),
+            tokens: None,
+        }
+    }
+}
+
+impl SchematicConfig {
+    /// The character written into cells that are blanked, such as the padding of short rows.
+    fn blank(&self) -> char {
+        self.blanks.chars().next().unwrap_or('.')
+    }
+}
+
+/// What to do with rows that are not all the same width. A blank line is a row with no columns,
+/// so it is padded, rejected or left outside the schematic like any other short row.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RaggedRows {
+    /// Pad short rows out to the widest row with the first of the blank characters.
+    #[default]
+    Pad,
+    /// Reject any row that is not as wide as the first.
+    Error,
+    /// Keep each row at its own width, with the cells past its end outside the schematic.
+    Allow,
+}
+
+impl RaggedRows {
+    fn check(&self, row: usize, width: usize, expected: usize) -> Result<(), ParseSchematicError> {
+        if *self == RaggedRows::Error && width != expected {
+            return Err(ParseSchematicError {
+                row,
+                col: width.min(expected),
+                kind: ParseSchematicErrorKind::RaggedRow { width, expected },
+            });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseSchematicErrorKind {
+    /// A number with these digits does not fit in the type it is parsed into.
+    NumberTooLarge(String),
+    /// A signed or fractional number, which only [`run_tokenized`] can total.
+    UnsupportedNumber(String),
+    /// A row is `width` columns wide when [`RaggedRows::Error`] needs `expected`.
+    RaggedRow { width: usize, expected: usize },
+}
+
+/// A schematic that could not be parsed, pointing at the 0-based row and column, in characters,
+/// where the problem is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSchematicError {
+    pub row: usize,
+    pub col: usize,
+    pub kind: ParseSchematicErrorKind,
+}
+
+impl Display for ParseSchematicError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "row {}, column {}: ", self.row, self.col)?;
+        match &self.kind {
+            ParseSchematicErrorKind::NumberTooLarge(digits) => write!(f, "number {digits} is too large"),
+            ParseSchematicErrorKind::UnsupportedNumber(text) => {
+                write!(f, "number {text} is not a whole number without a sign")
+            }
+            ParseSchematicErrorKind::RaggedRow { width, expected } => {
+                write!(f, "row is {width} columns wide, expected {expected}")
+            }
+        }
+    }
+}
+
+impl Error for ParseSchematicError {}
+
+/// Anything that can stop a day 3 solver from producing an answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchematicError {
+    Parse(ParseSchematicError),
+    Overflow(OverflowError),
+    /// The schematic could not be read, or was not valid UTF-8.
+    Io(io::ErrorKind),
+    /// A [`SchematicStream`] only has the rows around the one it is solving, so it cannot reach
+    /// across edges other than [`Edges::Bounded`].
+    UnboundedEdges(Edges),
+}
+
+impl Display for SchematicError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchematicError::Parse(error) => write!(f, "{error}"),
+            SchematicError::Overflow(error) => write!(f, "{error}"),
+            SchematicError::Io(kind) => write!(f, "could not read the schematic: {kind}"),
+            SchematicError::UnboundedEdges(edges) => {
+                write!(f, "a streamed schematic can only have bounded edges, not {edges:?}")
+            }
+        }
+    }
+}
+
+impl Error for SchematicError {}
+
+impl From<ParseSchematicError> for SchematicError {
+    fn from(error: ParseSchematicError) -> Self {
+        SchematicError::Parse(error)
+    }
+}
+
+impl From<OverflowError> for SchematicError {
+    fn from(error: OverflowError) -> Self {
+        SchematicError::Overflow(error)
+    }
 }
 
-struct SchematicEntryBuilderLine {
-    line: Vec<SchematicEntryBuilder>,
+impl From<io::Error> for SchematicError {
+    fn from(error: io::Error) -> Self {
+        SchematicError::Io(error.kind())
+    }
 }
 
-impl From<String> for SchematicEntryBuilderLine {
-    fn from(value: String) -> Self {
-        let mut current_state = SchematicParserState::from(value.as_bytes()[0]);
-        let mut current_value = Vec::<u8>::new();
-        let mut line: Vec<SchematicEntryBuilder> = Vec::new();
-        for &character in value.as_bytes().iter() {
-            let next_state = SchematicParserState::from(character);
-            if current_state != next_state {
-                match current_state {
+impl<V: NumberValue> SchematicEntryBuilderLine<V> {
+    /// Splits a row into entries. Columns are counted in `char`s, so a multi-byte character is a
+    /// single cell; a row that is empty has no entries at all.
+    ///
+    /// With [`SchematicConfig::tokens`] set, numbers and operators are matched as tokens first,
+    /// and an operator becomes one symbol per character it covers.
+    fn parse(value: &str, row: usize, config: &SchematicConfig) -> Result<Self, ParseSchematicError> {
+        let mut line: Vec<SchematicEntryBuilder<V>> = Vec::new();
+        let chars = value.chars().collect::<Vec<char>>();
+        let mut col = 0;
+        while col < chars.len() {
+            let character = chars[col];
+            let token = config.tokens.as_ref().and_then(|tokens| tokens.match_at(&chars, col));
+            let width = match token {
+                Some(Token::Number(width)) => {
+                    let text = chars[col..col + width].iter().collect::<String>();
+                    let value = parse_number(&text, row, col)?;
+                    line.push(SchematicEntryBuilder::Number { value, width: width as u32 });
+                    width
+                }
+                Some(Token::Operator(width)) => {
+                    line.extend(chars[col..col + width].iter().map(|&symbol| SchematicEntryBuilder::Symbol(symbol)));
+                    width
+                }
+                None => match SchematicParserState::classify(character, config) {
                     SchematicParserState::Number => {
-                        // println!("current value {:?}", current_value);
-                        // println!("Current Value as string: {:?}", String::from_utf8(current_value.clone()));
-                        line.push(SchematicEntryBuilder::Number {
-                            value: String::from_utf8(current_value.clone())
-                                .unwrap()
-                                .parse()
-                                .unwrap(),
-                            width: current_value.len() as u32,
-                        });
+                        let width = chars[col..].iter().take_while(|c| c.is_ascii_digit()).count();
+                        let digits = chars[col..col + width].iter().collect::<String>();
+                        let value = parse_number(&digits, row, col)?;
+                        line.push(SchematicEntryBuilder::Number { value, width: width as u32 });
+                        width
                     }
                     SchematicParserState::Dot => {
-                        line.push(SchematicEntryBuilder::BlankSpace {
-                            width: current_value.len() as u32,
-                        });
+                        // Runs of different blank characters are kept apart so they can be rendered as written.
+                        let width = chars[col..].iter().take_while(|&&c| c == character).count();
+                        line.push(SchematicEntryBuilder::BlankSpace { width: width as u32, character });
+                        width
                     }
                     SchematicParserState::Symbol => {
-                        for _ in 0..current_value.len() {
-                            line.push(SchematicEntryBuilder::Symbol);
-                        }
-                    },
+                        line.push(SchematicEntryBuilder::Symbol(character));
+                        1
+                    }
                     SchematicParserState::Gear => {
-                        for _ in 0..current_value.len() {
-                            line.push(SchematicEntryBuilder::Gear);
-                        }
+                        line.push(SchematicEntryBuilder::Gear(character));
+                        1
                     }
-                }
-                current_value.clear();
-            }
-            current_value.push(character);
-            current_state = next_state;
-        }
-        match current_state {
-            SchematicParserState::Number => {
-                line.push(SchematicEntryBuilder::Number {
-                    value: String::from_utf8(current_value.clone())
-                        .unwrap()
-                        .parse()
-                        .unwrap(),
-                    width: current_value.len() as u32,
-                });
-            }
-            SchematicParserState::Dot => {
-                line.push(SchematicEntryBuilder::BlankSpace {
-                    width: current_value.len() as u32,
-                });
-            }
-            SchematicParserState::Symbol => {
-                for _ in 0..current_value.len() {
-                    line.push(SchematicEntryBuilder::Symbol);
-                }
-            }
-            SchematicParserState::Gear => {
-                for _ in 0..current_value.len() {
-                    line.push(SchematicEntryBuilder::Gear);
-                }
-            }
+                },
+            };
+            col += width;
         }
-        Self { line }
+        Ok(Self { line })
+    }
+}
+
+impl<V> SchematicEntryBuilderLine<V> {
+    /// The number of columns in the row.
+    fn width(&self) -> usize {
+        self.line
+            .iter()
+            .map(|builder| match *builder {
+                SchematicEntryBuilder::BlankSpace { width, character: _ } => width as usize,
+                SchematicEntryBuilder::Number { value: _, width } => width as usize,
+                SchematicEntryBuilder::Symbol(_) | SchematicEntryBuilder::Gear(_) => 1,
+            })
+            .sum()
     }
 }
 
@@ -92,22 +304,23 @@ enum SchematicParserState {
     Gear
 }
 
-fn is_numeric(byte: u8) -> bool {
-    assert!(b'0' < b'9');
-    byte >= b'0' && byte <= b'9'
+/// Parses the text of a number that starts at `row`, `col`.
+fn parse_number<V: NumberValue>(text: &str, row: usize, col: usize) -> Result<V, ParseSchematicError> {
+    V::parse_number(text).map_err(|kind| ParseSchematicError { row, col, kind })
 }
 
-impl From<u8> for SchematicParserState {
-    fn from(value: u8) -> Self {
-        if is_numeric(value) {
+impl SchematicParserState {
+    fn classify(character: char, config: &SchematicConfig) -> Self {
+        if character.is_ascii_digit() {
             SchematicParserState::Number
-        } else if value == b'.' {
+        } else if config.blanks.contains(character) {
             SchematicParserState::Dot
-        } else if value == b'*' {
+        } else if config.gears.contains(character) {
             SchematicParserState::Gear
-        }
-        else {
+        } else if config.symbols.as_ref().is_none_or(|symbols| symbols.contains(character)) {
             SchematicParserState::Symbol
+        } else {
+            SchematicParserState::Dot
         }
     }
 }
@@ -127,259 +340,402 @@ impl SchematicPosition {
 #[derive(Debug, Clone, Copy)]
 enum SchematicEntry {
     Number {
-        value: u32,
         first_pos: SchematicPosition,
         last_pos: SchematicPosition,
     },
-    #[allow(dead_code)]
     Symbol{ pos: SchematicPosition },
-    #[allow(dead_code)]
     Gear{ pos: SchematicPosition },
 }
 
-enum SearchBoxState {
-    Top,
-    Right,
-    Bottom,
-    Left,
-    Finished
-}
-
-struct SchematicSearchBox {
-    top_left: SchematicPosition,
-    bottom_right: SchematicPosition,
-    current_position: SchematicPosition,
-    state: SearchBoxState
-}
-
-impl Iterator for SchematicSearchBox {
-    type Item = SchematicPosition;
-    fn next(&mut self) -> Option<Self::Item> {
-        let next_state: SearchBoxState;
-        let result = self.current_position.clone();
-        match self.state {
-            SearchBoxState::Finished => return None,
-            SearchBoxState::Top => {
-                self.current_position.col += 1;
-                if self.current_position.col == self.bottom_right.col {
-                    next_state = SearchBoxState::Right;
-                } else {
-                    next_state = SearchBoxState::Top;
-                }
-            },
-            SearchBoxState::Right => {
-                self.current_position.row += 1;
-                if self.current_position.row == self.bottom_right.row {
-                    next_state = SearchBoxState::Bottom;
-                } else {
-                    next_state = SearchBoxState::Right;
-                }
-            },
-            SearchBoxState::Bottom => {
-                self.current_position.col -= 1;
-                if self.current_position.col == self.top_left.col {
-                    next_state = SearchBoxState::Left;
-                } else {
-                    next_state = SearchBoxState::Bottom;
-                }
-            },
-            SearchBoxState::Left => {
-                self.current_position.row -= 1;
-                if self.current_position.row == self.top_left.row {
-                    next_state = SearchBoxState::Finished;
-                } else {
-                    next_state = SearchBoxState::Left;
-                }
-            }
+impl SchematicEntry {
+    /// The first and last cells the entry covers.
+    fn span(&self) -> (SchematicPosition, SchematicPosition) {
+        match *self {
+            SchematicEntry::Number { first_pos, last_pos } => (first_pos, last_pos),
+            SchematicEntry::Symbol { pos } | SchematicEntry::Gear { pos } => (pos, pos),
         }
-        self.state = next_state;
-        Some(result)
     }
 }
 
-impl<'a> IntoIterator for &'a SchematicEntry {
-    type Item = SchematicPosition;
-    type IntoIter = SchematicSearchBox;
+/// What occupies a single cell of the schematic. Number cells hold the index of their number in
+/// [`Schematic::numbers`] so that each number is only stored once; every other cell keeps the
+/// character it was parsed from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Cell {
+    Blank(char),
+    Number(u32),
+    Symbol(char),
+    Gear(char),
+    /// Past the end of a short row that was not padded, or off the edge of the grid.
+    Outside,
+}
 
-    fn into_iter(self) -> Self::IntoIter {
-        match *self {
-            SchematicEntry::Number { value:_, first_pos, last_pos } => {
-                let mut top_left = first_pos.clone();
-                top_left.row -= 1;
-                top_left.col -= 1;
-                let mut bottom_right = last_pos.clone();
-                bottom_right.row += 1;
-                bottom_right.col += 1;
-                Self::IntoIter {
-                    top_left,
-                    bottom_right,
-                    current_position: top_left.clone(),
-                    state: SearchBoxState::Top,
-                }
-            },
-            SchematicEntry::Gear { pos } => {
-                let mut top_left = pos.clone();
-                top_left.row -= 1;
-                top_left.col -= 1;
-                let mut bottom_right = pos.clone();
-                bottom_right.row += 1;
-                bottom_right.col += 1;
-                Self::IntoIter {
-                    top_left,
-                    bottom_right,
-                    current_position: top_left.clone(),
-                    state: SearchBoxState::Top,
-                }
-            }
-            _ => panic!("Only call on number or gear")
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct SchematicNumber<V = u64> {
+    value: V,
+    first_pos: SchematicPosition,
+    last_pos: SchematicPosition,
+}
+
+impl SchematicNumber {
+    fn width(&self) -> usize {
+        (self.last_pos.col - self.first_pos.col + 1) as usize
+    }
+
+    /// The digits as written, including any leading zeros.
+    fn digits(&self) -> String {
+        format!("{:0width$}", self.value, width = self.width())
+    }
+}
+
+impl<V> From<&SchematicNumber<V>> for SchematicEntry {
+    fn from(number: &SchematicNumber<V>) -> Self {
+        SchematicEntry::Number {
+            first_pos: number.first_pos,
+            last_pos: number.last_pos,
         }
     }
 }
 
-struct Schematic {
-    schematic_map: HashMap<SchematicPosition, SchematicEntry>,
+/// A dense row-major grid of cells. Rows shorter than the widest row are padded with blanks, or
+/// left outside the schematic, according to the [`RaggedRows`] policy it was parsed with.
+///
+/// Numbers are `u64`s, except in the schematics that [`run_tokenized`] parses into [`Decimal`]s.
+pub struct Schematic<V = u64> {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+    numbers: Vec<SchematicNumber<V>>,
+    gear_adjacency: GearAdjacency,
+    neighbourhood: Neighbourhood,
+    edges: Edges,
+}
+
+// Only this module can parse a schematic, so the bound being private hides nothing.
+#[allow(private_bounds)]
+impl<V: NumberValue> Schematic<V> {
+    fn parse(input: &PuzzleInput<String>, config: &SchematicConfig) -> Result<Self, ParseSchematicError> {
+        let lines = input
+            .into_iter()
+            .enumerate()
+            .map(|(row, line)| SchematicEntryBuilderLine::parse(line, row, config))
+            .collect::<Result<Vec<SchematicEntryBuilderLine<V>>, ParseSchematicError>>()?;
+        let widths = lines.iter().map(SchematicEntryBuilderLine::width).collect::<Vec<usize>>();
+        for (row, &width) in widths.iter().enumerate() {
+            config.ragged_rows.check(row, width, widths[0])?;
+        }
+        let mut schematic = Self::from(lines.as_slice());
+        schematic.gear_adjacency = config.gear_adjacency;
+        schematic.neighbourhood = config.neighbourhood;
+        schematic.edges = config.edges;
+        if config.ragged_rows == RaggedRows::Pad {
+            for (row, width) in widths.into_iter().enumerate() {
+                let row_start = row * schematic.width;
+                schematic.cells[row_start + width..row_start + schematic.width].fill(Cell::Blank(config.blank()));
+            }
+        }
+        Ok(schematic)
+    }
 }
 
-impl From<PuzzleInput<SchematicEntryBuilderLine>> for Schematic {
-    fn from(value: PuzzleInput<SchematicEntryBuilderLine>) -> Self {
-        let mut schematic_map: HashMap<SchematicPosition, SchematicEntry> = HashMap::new();
-        for (row, line) in value.into_iter().enumerate() {
-            let mut current_col: isize = 0;
+impl<V: Copy> From<&[SchematicEntryBuilderLine<V>]> for Schematic<V> {
+    fn from(value: &[SchematicEntryBuilderLine<V>]) -> Self {
+        let width = value.iter().map(SchematicEntryBuilderLine::width).max().unwrap_or(0);
+        let height = value.len();
+        let mut cells = vec![Cell::Outside; width * height];
+        let mut numbers = vec![];
+        for (row, line) in value.iter().enumerate() {
+            let row_start = row * width;
+            let mut current_col: usize = 0;
             for builder in line.line.iter() {
                 match *builder {
-                    SchematicEntryBuilder::BlankSpace { width } => {
-                        current_col += width as isize;
+                    SchematicEntryBuilder::BlankSpace { width, character } => {
+                        let start = row_start + current_col;
+                        cells[start..start + width as usize].fill(Cell::Blank(character));
+                        current_col += width as usize;
                     }
                     SchematicEntryBuilder::Number { value, width } => {
-                        let entry = SchematicEntry::Number {
+                        let id = u32::try_from(numbers.len()).expect("Too many numbers in schematic");
+                        numbers.push(SchematicNumber {
                             value,
-                            first_pos: SchematicPosition::new(row as isize, current_col),
+                            first_pos: SchematicPosition::new(row as isize, current_col as isize),
                             last_pos: SchematicPosition::new(
                                 row as isize,
-                                current_col + (width as isize) - 1,
+                                (current_col + width as usize) as isize - 1,
                             ),
-                        };
-                        for c in 0..width {
-                            assert!(schematic_map.insert(
-                                SchematicPosition::new(row as isize, current_col + (c as isize)),
-                                entry.clone(),
-                            ).is_none());
-                        }
-                        current_col += width as isize;
+                        });
+                        let start = row_start + current_col;
+                        cells[start..start + width as usize].fill(Cell::Number(id));
+                        current_col += width as usize;
                     }
-                    SchematicEntryBuilder::Symbol => {
-                        assert!(schematic_map.insert(
-                            SchematicPosition::new(row as isize, current_col),
-                            SchematicEntry::Symbol{ pos: SchematicPosition::new(row as isize, current_col) },
-                        ).is_none());
+                    SchematicEntryBuilder::Symbol(symbol) => {
+                        cells[row_start + current_col] = Cell::Symbol(symbol);
                         current_col += 1;
-                    },
-                    SchematicEntryBuilder::Gear => {
-                        assert!(schematic_map.insert(
-                            SchematicPosition::new(row as isize, current_col),
-                            SchematicEntry::Gear{ pos: SchematicPosition::new(row as isize, current_col) },
-                        ).is_none());
+                    }
+                    SchematicEntryBuilder::Gear(gear) => {
+                        cells[row_start + current_col] = Cell::Gear(gear);
                         current_col += 1;
                     }
                 }
             }
         }
-        Self { schematic_map }
+        Self {
+            width,
+            height,
+            cells,
+            numbers,
+            gear_adjacency: GearAdjacency::Exactly(2),
+            neighbourhood: Neighbourhood::default(),
+            edges: Edges::default(),
+        }
     }
 }
 
-impl Schematic {
-    pub fn get_part_numbers(&self) -> Vec<u32> {
-        let mut result = vec![];
-        for entry in self.schematic_map.values() {
-            match *entry {
-                SchematicEntry::Number { value, first_pos: _, last_pos: _ } => {
-                    for cursor in entry.into_iter() {
-                        if self.schematic_map.get(&cursor).is_some_and(|x| {
-                            match *x {
-                                SchematicEntry::Symbol{ pos: _ } => true,
-                                SchematicEntry::Gear { pos: _ } => true,
-                                _ => false
-                            }
-                        }) {
-                            result.push(value);
-                            break;
-                        }
-                    }
-                },
-                _ => {}
-            }
+impl<V: Copy> Schematic<V> {
+    fn cell(&self, pos: SchematicPosition) -> Cell {
+        if pos.row < 0 || pos.col < 0 || pos.row as usize >= self.height || pos.col as usize >= self.width {
+            return Cell::Outside;
         }
-        result
-    }
-
-    pub fn get_gear_ratios(&self) -> Vec<u32> {
-        let mut result: Vec<u32> = vec![];
-        for entry in self.schematic_map.values().filter(|&e| matches!(e, SchematicEntry::Gear { pos: _ })) {
-            let mut current_ratio = 1;
-            let mut num_elements_found = 0;
-            let mut found_elements = HashSet::new();
-            for cursor in entry.into_iter() {
-                if let Some(val) = self.schematic_map.get(&cursor) {
-                    match *val {
-                         SchematicEntry::Number { value, first_pos, last_pos: _ } => {
-                            if !found_elements.contains(&first_pos) {
-                                found_elements.insert(first_pos);
-                                current_ratio *= value;
-                                num_elements_found += 1;
-                                if num_elements_found > 2 { break; }
-                            }
-                         },
-                         _ => {}
-                    }
+        self.cells[pos.row as usize * self.width + pos.col as usize]
+    }
+
+    /// The cells of the grid in the neighbourhood of `entry`, reaching past the edges of the grid
+    /// according to its edge policy.
+    fn neighbours(&self, entry: &SchematicEntry) -> impl Iterator<Item = SchematicPosition> {
+        let (first, last) = entry.span();
+        self.neighbourhood
+            .around((first.row, first.col), (last.row, last.col))
+            .on_grid(self.height, self.width, self.edges)
+            .map(|(row, col)| SchematicPosition::new(row, col))
+    }
+
+    /// Like [`Schematic::neighbours`], but with every mirror image of a cell kept apart, along
+    /// with the copy of the grid it is in.
+    fn neighbour_images(&self, entry: &SchematicEntry) -> impl Iterator<Item = (SchematicPosition, (isize, isize))> {
+        let (first, last) = entry.span();
+        self.neighbourhood
+            .around((first.row, first.col), (last.row, last.col))
+            .on_grid_images(self.height, self.width, self.edges)
+            .map(|((row, col), image)| (SchematicPosition::new(row, col), image))
+    }
+
+    fn is_part_number(&self, number: &SchematicNumber<V>) -> bool {
+        self.neighbours(&SchematicEntry::from(number))
+            .any(|cursor| matches!(self.cell(cursor), Cell::Symbol(_) | Cell::Gear(_)))
+    }
+
+    /// Ids of the distinct numbers around `pos`, from the top left, stopping early once there are
+    /// more than the gear adjacency rule could accept. A number seen in a mirror as well as
+    /// directly is listed once for each image of it.
+    fn gear_numbers(&self, pos: SchematicPosition) -> Vec<u32> {
+        let mut found = vec![];
+        for (cursor, image) in self.neighbour_images(&SchematicEntry::Gear { pos }) {
+            if let Cell::Number(id) = self.cell(cursor) {
+                if !found.contains(&(id, image)) {
+                    found.push((id, image));
+                    if self.gear_adjacency.limit().is_some_and(|limit| found.len() > limit) { break; }
                 }
             }
-            if num_elements_found == 2 {
-                result.push(current_ratio);
-            }
         }
-        result
+        found.into_iter().map(|(id, _)| id).collect()
     }
 
-    pub fn _print_numbers(&self) {
-        for entry in self.schematic_map.values() {
-            match *entry {
-                SchematicEntry::Number { value, first_pos, last_pos } => {
-                    println!("value: {}, first_pos: {:?}, last_pos: {:?}", value, first_pos, last_pos);
-                },
-                SchematicEntry::Symbol { pos } => {
-                    println!("pos: {:?}", pos);
-                },
-                _ => {}
+    fn is_valid_gear(&self, pos: SchematicPosition) -> bool {
+        matches!(self.cell(pos), Cell::Gear(_)) && self.gear_adjacency.matches(self.gear_numbers(pos).len())
+    }
+
+    /// The positions of the gear cells, in row-major order.
+    fn gear_positions(&self) -> impl Iterator<Item = SchematicPosition> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| matches!(cell, Cell::Gear(_)))
+            .map(|(index, _)| SchematicPosition::new((index / self.width) as isize, (index % self.width) as isize))
+    }
+}
+
+impl Schematic {
+    /// Part numbers in row-major order of their first digit.
+    pub fn get_part_numbers(&self) -> Vec<u64> {
+        self.numbers
+            .iter()
+            .filter(|number| self.is_part_number(number))
+            .map(|number| number.value)
+            .collect()
+    }
+
+    /// Gear ratios in row-major order of their gear.
+    pub fn get_gear_ratios<A: Answer>(&self) -> Result<Vec<A>, OverflowError> {
+        let mut result: Vec<A> = vec![];
+        for pos in self.gear_positions() {
+            let found_ids = self.gear_numbers(pos);
+            if self.gear_adjacency.matches(found_ids.len()) {
+                result.push(A::try_product(
+                    found_ids.into_iter().map(|id| A::from_u64(self.numbers[id as usize].value)),
+                )?);
             }
         }
+        Ok(result)
     }
 }
 
-pub fn run_part_1(input: &mut File) -> u32 {
-    let puzzle_input: PuzzleInput<SchematicEntryBuilderLine> = PuzzleInput::from_file(input).unwrap();
-    let schematic = Schematic::from(puzzle_input);
-    // schematic.print_numbers();
-    schematic.get_part_numbers().iter().sum()
+pub fn run_part_1<A: Answer>(input: &mut File) -> Result<A, SchematicError> {
+    run_part_1_with_config(input, &SchematicConfig::default())
 }
 
-pub fn run_part_2(input: &mut File) -> u32 {
-    let puzzle_input: PuzzleInput<SchematicEntryBuilderLine> = PuzzleInput::from_file(input).unwrap();
-    let schematic = Schematic::from(puzzle_input);
-    // schematic.print_numbers();
-    schematic.get_gear_ratios().iter().sum()
+pub fn run_part_1_with_config<A: Answer>(input: &mut File, config: &SchematicConfig) -> Result<A, SchematicError> {
+    let puzzle_input: PuzzleInput<String> = PuzzleInput::rows_from_file(input)?;
+    let schematic = Schematic::parse(&puzzle_input, config)?;
+    Ok(A::try_sum(schematic.get_part_numbers().into_iter().map(A::from_u64))?)
+}
+
+pub fn run_part_2<A: Answer>(input: &mut File) -> Result<A, SchematicError> {
+    run_part_2_with_config(input, &SchematicConfig::default())
+}
+
+pub fn run_part_2_with_config<A: Answer>(input: &mut File, config: &SchematicConfig) -> Result<A, SchematicError> {
+    let puzzle_input: PuzzleInput<String> = PuzzleInput::rows_from_file(input)?;
+    let schematic = Schematic::parse(&puzzle_input, config)?;
+    Ok(A::try_sum(schematic.get_gear_ratios()?)?)
 }
 
 #[cfg(test)]
 mod test {
+    use std::io::ErrorKind;
+
     use crate::common::puzzle_input_reader::test::MockFile;
 
-    use super::{ run_part_1, run_part_2 };
+    use crate::common::answer::{BigUint, OverflowError};
+    use crate::common::grid::{Edges, Neighbourhood, NeighbourhoodShape};
+
+    use super::{ run_part_1, run_part_1_with_config, run_part_2, run_part_2_with_config, GearAdjacency, ParseSchematicError, ParseSchematicErrorKind, RaggedRows, Schematic, SchematicConfig, SchematicEntryBuilderLine, SchematicError };
+
+    #[test]
+    fn test_custom_characters() {
+        let config = SchematicConfig {
+            blanks: String::from(". "),
+            symbols: Some(String::from("#")),
+            gears: String::from("x"),
+            gear_adjacency: GearAdjacency::Exactly(2),
+            ..Default::default()
+        };
+        let content = "12 .-5..\n..x3#...\n7$......";
+        let mut input = MockFile::with_contents(content);
+        assert_eq!(run_part_1_with_config::<u64>(input.get_file(), &config), Ok(12 + 3 + 5));
+        let mut input = MockFile::with_contents(content);
+        assert_eq!(run_part_2_with_config::<u64>(input.get_file(), &config), Ok(12 * 3));
+        let mut input = MockFile::with_contents(content);
+        assert_eq!(run_part_1::<u64>(input.get_file()), Ok(12 + 5 + 3 + 7));
+    }
+
+    #[test]
+    fn test_neighbourhoods() {
+        let tests = [
+            ("1.\n.#", NeighbourhoodShape::Chebyshev, 1, 1),
+            ("1.\n.#", NeighbourhoodShape::Manhattan, 1, 0),
+            ("1.\n.#", NeighbourhoodShape::Manhattan, 2, 1),
+            ("1.\n.#", NeighbourhoodShape::VonNeumann, 5, 0),
+            ("1.#", NeighbourhoodShape::VonNeumann, 1, 0),
+            ("1.#", NeighbourhoodShape::VonNeumann, 2, 1),
+            ("12...\n.....\n....#", NeighbourhoodShape::Chebyshev, 2, 0),
+            ("12...\n.....\n....#", NeighbourhoodShape::Chebyshev, 3, 12),
+            ("12...\n.....\n....#", NeighbourhoodShape::Manhattan, 4, 0),
+            ("12...\n.....\n....#", NeighbourhoodShape::Manhattan, 5, 12),
+            ("5", NeighbourhoodShape::Chebyshev, 0, 0),
+        ];
+        for (content, shape, radius, expected_output) in tests {
+            let config = SchematicConfig { neighbourhood: Neighbourhood::new(shape, radius), ..Default::default() };
+            let mut input = MockFile::with_contents(content);
+            let output = run_part_1_with_config::<u64>(input.get_file(), &config).unwrap();
+            assert_eq!(output, expected_output, "{content:?} {shape:?} {radius}");
+        }
+
+        // With a radius of 2 the gear reaches both numbers.
+        let config = SchematicConfig {
+            neighbourhood: Neighbourhood::new(NeighbourhoodShape::Chebyshev, 2),
+            ..Default::default()
+        };
+        let mut input = MockFile::with_contents("3....\n..*..\n....4");
+        assert_eq!(run_part_2_with_config::<u64>(input.get_file(), &config), Ok(12));
+        let mut input = MockFile::with_contents("3....\n..*..\n....4");
+        assert_eq!(run_part_2::<u64>(input.get_file()), Ok(0));
+    }
+
+    #[test]
+    fn test_edges() {
+        let tests = [
+            ("1...*\n.....\n.....", Edges::Bounded, 0),
+            ("1...*\n.....\n.....", Edges::Torus, 1),
+            ("1...*\n.....\n.....", Edges::Mirrored, 0),
+            ("1....\n.....\n...*.", Edges::Torus, 0),
+            ("1....\n.....\n...*.", Edges::Mirrored, 0),
+            ("1*", Edges::Mirrored, 1),
+            // The number reaches the symbol across both edges, but only counts once.
+            ("1*", Edges::Torus, 1),
+        ];
+        for (content, edges, expected_output) in tests {
+            let config = SchematicConfig { edges, ..Default::default() };
+            let mut input = MockFile::with_contents(content);
+            let output = run_part_1_with_config::<u64>(input.get_file(), &config).unwrap();
+            assert_eq!(output, expected_output, "{content:?} {edges:?}");
+        }
+
+        // The gear in the top left corner touches numbers wrapped around from the right and bottom.
+        let config = SchematicConfig { edges: Edges::Torus, ..Default::default() };
+        let mut input = MockFile::with_contents("*...1\n.....\n2....");
+        assert_eq!(run_part_1_with_config::<u64>(input.get_file(), &config), Ok(3));
+        let mut input = MockFile::with_contents("*...1\n.....\n2....");
+        assert_eq!(run_part_2_with_config::<u64>(input.get_file(), &config), Ok(2));
+        let mut input = MockFile::with_contents("*...1\n.....\n2....");
+        assert_eq!(run_part_2::<u64>(input.get_file()), Ok(0));
+
+        // The gear sees the 2 beside it and its image in the mirror above, which makes two numbers.
+        let config = SchematicConfig { edges: Edges::Mirrored, ..Default::default() };
+        let mut input = MockFile::with_contents("2*.\n...\n...");
+        assert_eq!(run_part_2_with_config::<u64>(input.get_file(), &config), Ok(2 * 2));
+        let mut input = MockFile::with_contents("2*.\n...\n...");
+        assert_eq!(run_part_2::<u64>(input.get_file()), Ok(0));
+        // Next to the 3 as well, the gear has one number too many.
+        let mut input = MockFile::with_contents("2*3\n...\n...");
+        assert_eq!(run_part_2_with_config::<u64>(input.get_file(), &config), Ok(0));
+        let mut input = MockFile::with_contents("2*3\n...\n...");
+        assert_eq!(run_part_2::<u64>(input.get_file()), Ok(2 * 3));
+    }
+
+    #[test]
+    fn test_gear_adjacency() {
+        let content = "1.2\n.*.\n3..\n...\n4*5";
+        let ratios = [
+            (GearAdjacency::Exactly(2), 4 * 5),
+            (GearAdjacency::Exactly(3), 2 * 3),
+            (GearAdjacency::AtLeast(2), 2 * 3 + 4 * 5),
+            (GearAdjacency::AtLeast(3), 2 * 3),
+            (GearAdjacency::Exactly(1), 0),
+        ];
+        for (gear_adjacency, expected_output) in ratios {
+            let config = SchematicConfig { gear_adjacency, ..Default::default() };
+            let mut input = MockFile::with_contents(content);
+            let output = run_part_2_with_config::<u64>(input.get_file(), &config).unwrap();
+            assert_eq!(output, expected_output, "{gear_adjacency:?}");
+        }
+    }
+
+    #[test]
+    fn test_overflow() {
+        let content = "9999999999*9999999999";
+        let mut input = MockFile::with_contents(content);
+        assert_eq!(run_part_1::<u64>(input.get_file()), Ok(19999999998));
+        let mut input = MockFile::with_contents(content);
+        assert_eq!(run_part_2::<u64>(input.get_file()), Err(SchematicError::Overflow(OverflowError { type_name: "u64" })));
+        let mut input = MockFile::with_contents(content);
+        let output = run_part_2::<BigUint>(input.get_file()).unwrap();
+        assert_eq!(output.to_string(), "99999999980000000001");
+    }
 
     #[test]
     fn test_sample_input_1() {
         let mut input = MockFile::with_contents("467..114..\n...*......\n..35..633.\n......#...\n617*......\n.....+.58.\n..592.....\n......755.\n...$.*....\n.664.598..");
-        let output = run_part_1(input.get_file());
+        let output = run_part_1::<u64>(input.get_file()).unwrap();
         let expected_output = 4361;
         assert_eq!(output, expected_output);
     }
@@ -387,11 +743,100 @@ mod test {
     #[test]
     fn test_sample_input_2() {
         let mut input = MockFile::with_contents("467..114..\n...*......\n..35..633.\n......#...\n617*......\n.....+.58.\n..592.....\n......755.\n...$.*....\n.664.598..");
-        let output = run_part_2(input.get_file());
+        let output = run_part_2::<u64>(input.get_file()).unwrap();
         let expected_output = 467835;
         assert_eq!(output, expected_output);
     }
 
+    #[test]
+    fn test_ragged_rows() {
+        let content = "1*\n23456\n..\n.......7";
+        let mut input = MockFile::with_contents(content);
+        assert_eq!(run_part_1::<u64>(input.get_file()), Ok(1 + 23456));
+        let mut input = MockFile::with_contents(content);
+        assert_eq!(run_part_2::<u64>(input.get_file()), Ok(23456));
+
+        for ragged_rows in [RaggedRows::Pad, RaggedRows::Allow] {
+            let config = SchematicConfig { ragged_rows, ..Default::default() };
+            let mut input = MockFile::with_contents(content);
+            assert_eq!(run_part_1_with_config::<u64>(input.get_file(), &config), Ok(1 + 23456));
+        }
+        let config = SchematicConfig { ragged_rows: RaggedRows::Error, ..Default::default() };
+        let mut input = MockFile::with_contents(content);
+        assert_eq!(
+            run_part_1_with_config::<u64>(input.get_file(), &config),
+            Err(SchematicError::Parse(ParseSchematicError {
+                row: 1,
+                col: 2,
+                kind: ParseSchematicErrorKind::RaggedRow { width: 5, expected: 2 },
+            }))
+        );
+        let mut input = MockFile::with_contents("1*\n.2");
+        assert_eq!(run_part_1_with_config::<u64>(input.get_file(), &config), Ok(3));
+    }
+
+    #[test]
+    fn test_blank_lines() {
+        // The blank line is a row of its own, so the symbol is two rows away from the number.
+        let content = "..1..\n\n..#..\n";
+        for ragged_rows in [RaggedRows::Pad, RaggedRows::Allow] {
+            let config = SchematicConfig { ragged_rows, ..Default::default() };
+            let mut input = MockFile::with_contents(content);
+            assert_eq!(run_part_1_with_config::<u64>(input.get_file(), &config), Ok(0), "{ragged_rows:?}");
+        }
+        let config = SchematicConfig { ragged_rows: RaggedRows::Error, ..Default::default() };
+        let mut input = MockFile::with_contents(content);
+        assert_eq!(
+            run_part_1_with_config::<u64>(input.get_file(), &config),
+            Err(SchematicError::Parse(ParseSchematicError {
+                row: 1,
+                col: 0,
+                kind: ParseSchematicErrorKind::RaggedRow { width: 0, expected: 5 },
+            }))
+        );
+        // Only the newline at the very end of the file does not start another row.
+        let mut input = MockFile::with_contents("..1..\n..#..\n");
+        assert_eq!(run_part_1_with_config::<u64>(input.get_file(), &config), Ok(1));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let config = SchematicConfig::default();
+        assert!(SchematicEntryBuilderLine::<u64>::parse("", 0, &config).unwrap().line.is_empty());
+
+        let mut input = MockFile::with_contents("..*\n.123456789012345678901234567890");
+        let error = run_part_1::<u64>(input.get_file()).unwrap_err();
+        assert_eq!(
+            error,
+            SchematicError::Parse(ParseSchematicError {
+                row: 1,
+                col: 1,
+                kind: ParseSchematicErrorKind::NumberTooLarge(String::from("123456789012345678901234567890")),
+            })
+        );
+        assert_eq!(error.to_string(), "row 1, column 1: number 123456789012345678901234567890 is too large");
+
+        let mut input = MockFile::with_bytes(b"1*\n\xff.");
+        assert_eq!(run_part_1::<u64>(input.get_file()), Err(SchematicError::Io(ErrorKind::InvalidData)));
+        let mut input = MockFile::with_bytes(b"1*\n\xff.");
+        assert!(matches!(
+            Schematic::from_file(input.get_file(), &config),
+            Err(SchematicError::Io(ErrorKind::InvalidData))
+        ));
+    }
+
+    #[test]
+    fn test_non_ascii() {
+        // Each character is one cell however many bytes it takes, so the numbers line up.
+        let content = "é..12\n..#..\n.3..→";
+        let config = SchematicConfig { ragged_rows: RaggedRows::Error, ..Default::default() };
+        let mut input = MockFile::with_contents(content);
+        assert_eq!(run_part_1_with_config::<u64>(input.get_file(), &config), Ok(3 + 12));
+        let config = SchematicConfig { gears: String::from("€"), ..config };
+        let mut input = MockFile::with_contents("1€2");
+        assert_eq!(run_part_2_with_config::<u64>(input.get_file(), &config), Ok(2));
+    }
+
     #[test]
     fn test_corners()  {
         let tests = vec![
@@ -569,7 +1014,7 @@ mod test {
         for (content, expected_output) in tests {
 
             let mut input = MockFile::with_contents(content);
-            let output = run_part_1(input.get_file());
+            let output = run_part_1::<u64>(input.get_file()).unwrap();
             assert_eq!(output, expected_output);
         }
     }
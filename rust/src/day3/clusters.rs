@@ -0,0 +1,236 @@
+use crate::common::answer::{Answer, OverflowError};
+
+use super::{AdjacencyGraph, NumberNode, Schematic, SymbolNode};
+
+/// The smallest rectangle of cells, inclusive at both ends, holding every entry of a cluster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub first_row: usize,
+    pub first_col: usize,
+    pub last_row: usize,
+    pub last_col: usize,
+}
+
+impl BoundingBox {
+    fn cover(&mut self, row: usize, first_col: usize, last_col: usize) {
+        self.first_row = self.first_row.min(row);
+        self.first_col = self.first_col.min(first_col);
+        self.last_row = self.last_row.max(row);
+        self.last_col = self.last_col.max(last_col);
+    }
+}
+
+/// A connected group of numbers and symbols: numbers joined through the symbols they share and
+/// symbols joined through the numbers they share. A number or symbol touching nothing is a
+/// cluster of its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cluster {
+    pub numbers: Vec<NumberNode>,
+    pub symbols: Vec<SymbolNode>,
+    pub bounds: BoundingBox,
+}
+
+impl Cluster {
+    /// How many numbers and symbols the cluster holds.
+    pub fn size(&self) -> usize {
+        self.numbers.len() + self.symbols.len()
+    }
+
+    pub fn total_value<A: Answer>(&self) -> Result<A, OverflowError> {
+        A::try_sum(self.numbers.iter().map(|number| A::from_u64(number.value)))
+    }
+}
+
+/// Follows `parents` to the root of `node`, halving the path on the way.
+fn find(parents: &mut [usize], mut node: usize) -> usize {
+    while parents[node] != node {
+        parents[node] = parents[parents[node]];
+        node = parents[node];
+    }
+    node
+}
+
+impl AdjacencyGraph {
+    /// The connected components of the graph, ordered by their first entry in row-major order.
+    /// Numbers and symbols within each cluster keep the graph's row-major order.
+    pub fn clusters(&self) -> Vec<Cluster> {
+        // Numbers are nodes `0..numbers.len()`, symbols follow them.
+        let num_numbers = self.numbers().len();
+        let mut parents = (0..num_numbers + self.symbols().len()).collect::<Vec<usize>>();
+        for &(number, symbol) in self.edges() {
+            let number_root = find(&mut parents, number);
+            let symbol_root = find(&mut parents, num_numbers + symbol);
+            parents[symbol_root] = number_root;
+        }
+
+        let mut clusters: Vec<Cluster> = vec![];
+        let mut cluster_ids = vec![None; parents.len()];
+        let mut keys = vec![];
+        let nodes = self
+            .numbers()
+            .iter()
+            .map(|number| (number.row, number.first_col, number.last_col))
+            .chain(
+                self.symbols()
+                    .iter()
+                    .map(|symbol| (symbol.row, symbol.col, symbol.col)),
+            );
+        for (node, (row, first_col, last_col)) in nodes.enumerate() {
+            let root = find(&mut parents, node);
+            let id = *cluster_ids[root].get_or_insert_with(|| {
+                clusters.push(Cluster {
+                    numbers: vec![],
+                    symbols: vec![],
+                    bounds: BoundingBox {
+                        first_row: row,
+                        first_col,
+                        last_row: row,
+                        last_col,
+                    },
+                });
+                keys.push((row, first_col));
+                clusters.len() - 1
+            });
+            let cluster = &mut clusters[id];
+            cluster.bounds.cover(row, first_col, last_col);
+            keys[id] = keys[id].min((row, first_col));
+            if node < num_numbers {
+                cluster.numbers.push(self.numbers()[node].clone());
+            } else {
+                cluster
+                    .symbols
+                    .push(self.symbols()[node - num_numbers].clone());
+            }
+        }
+
+        let mut clusters = keys.into_iter().zip(clusters).collect::<Vec<_>>();
+        clusters.sort_by_key(|(key, _)| *key);
+        clusters.into_iter().map(|(_, cluster)| cluster).collect()
+    }
+}
+
+impl Schematic {
+    /// The clusters of numbers and symbols linked under the schematic's neighbourhood.
+    pub fn clusters(&self) -> Vec<Cluster> {
+        self.graph().clusters()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::answer::BigUint;
+    use crate::common::grid::Edges;
+    use crate::common::puzzle_input_reader::test::MockFile;
+    use crate::day3::{Schematic, SchematicConfig};
+
+    use super::BoundingBox;
+
+    fn schematic(content: &str) -> Schematic {
+        schematic_with_config(content, &SchematicConfig::default())
+    }
+
+    fn schematic_with_config(content: &str, config: &SchematicConfig) -> Schematic {
+        let mut input = MockFile::with_contents(content);
+        Schematic::from_file(input.get_file(), config).unwrap()
+    }
+
+    #[test]
+    fn test_sample_clusters() {
+        let clusters = schematic(
+            "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..",
+        )
+        .clusters();
+        let summary = clusters
+            .iter()
+            .map(|cluster| (cluster.size(), cluster.total_value::<u64>().unwrap()))
+            .collect::<Vec<(usize, u64)>>();
+        assert_eq!(
+            summary,
+            vec![
+                (3, 502),
+                (1, 114),
+                (2, 633),
+                (2, 617),
+                (2, 592),
+                (1, 58),
+                (3, 1353),
+                (2, 664)
+            ]
+        );
+        assert_eq!(
+            clusters[6].bounds,
+            BoundingBox {
+                first_row: 7,
+                first_col: 5,
+                last_row: 9,
+                last_col: 8
+            }
+        );
+    }
+
+    #[test]
+    fn test_chained_clusters() {
+        // The two symbols share the 12, so everything joins into one cluster.
+        let clusters = schematic("3*12#\n....9\n@....").clusters();
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].size(), 5);
+        assert_eq!(
+            clusters[0].total_value::<BigUint>().unwrap().to_string(),
+            "24"
+        );
+        assert_eq!(
+            clusters[0].bounds,
+            BoundingBox {
+                first_row: 0,
+                first_col: 0,
+                last_row: 1,
+                last_col: 4
+            }
+        );
+        assert_eq!(clusters[1].symbols[0].character, '@');
+        assert!(clusters[1].numbers.is_empty());
+        assert!(schematic("...").clusters().is_empty());
+    }
+
+    #[test]
+    fn test_edges() {
+        let content = "*...1\n.....\n2...#";
+        let sizes = |edges| {
+            let config = SchematicConfig {
+                edges,
+                ..Default::default()
+            };
+            schematic_with_config(content, &config)
+                .clusters()
+                .iter()
+                .map(|cluster| (cluster.size(), cluster.total_value::<u64>().unwrap()))
+                .collect::<Vec<(usize, u64)>>()
+        };
+        // On a torus the corners all touch, so everything is one cluster.
+        assert_eq!(sizes(Edges::Torus), vec![(4, 3)]);
+        // A mirror only shows images of entries already in reach, which cannot join anything new,
+        // so the corners stay apart.
+        assert_eq!(sizes(Edges::Mirrored), vec![(1, 0), (1, 1), (1, 2), (1, 0)]);
+        // On a single column the number is only linked to the symbol once.
+        let config = SchematicConfig {
+            edges: Edges::Mirrored,
+            ..Default::default()
+        };
+        let clusters = schematic_with_config("7\n#", &config).clusters();
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].size(), 2);
+        assert_eq!(
+            schematic_with_config("7\n#", &config).graph().edges().len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,624 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::fs::File;
+
+use crate::common::answer::{Answer, BigUint, OverflowError};
+use crate::common::puzzle_input_reader::PuzzleInput;
+
+use super::{
+    parse_number, Cell, ParseSchematicError, RenderOptions, Schematic, SchematicConfig,
+    SchematicEntry, SchematicError, SchematicNumber, SchematicParserState, SchematicPosition,
+};
+
+/// The ways an edit can be rejected. A rejected edit leaves the schematic as it was.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditSchematicError {
+    OutOfBounds {
+        row: usize,
+        col: usize,
+    },
+    /// [`SchematicEditor::set_digit`] was given a value that is not a single digit.
+    NotADigit(u8),
+    /// [`SchematicEditor::place_symbol`] was given a digit, which needs
+    /// [`SchematicEditor::set_digit`] instead.
+    DigitSymbol(char),
+    /// The edit would join digits into a number that does not parse.
+    Parse(ParseSchematicError),
+}
+
+impl Display for EditSchematicError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EditSchematicError::OutOfBounds { row, col } => {
+                write!(f, "row {row}, column {col} is outside the schematic")
+            }
+            EditSchematicError::NotADigit(digit) => write!(f, "{digit} is not a digit"),
+            EditSchematicError::DigitSymbol(symbol) => {
+                write!(f, "{symbol} is a digit, not a symbol")
+            }
+            EditSchematicError::Parse(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for EditSchematicError {}
+
+impl From<ParseSchematicError> for EditSchematicError {
+    fn from(error: ParseSchematicError) -> Self {
+        EditSchematicError::Parse(error)
+    }
+}
+
+/// A schematic that can be edited a cell at a time while keeping its part number sum and gear
+/// ratio sum up to date.
+///
+/// Each edit only revisits the numbers and gears within reach of the edited cell, so an edit costs
+/// the same however large the schematic is. Numbers are not kept in row-major order once the
+/// schematic has been edited.
+///
+/// The sums are kept exactly, so that an edit can take back out a contribution that once took
+/// them past what an answer type holds, and are only checked against the answer type they are
+/// read as.
+pub struct SchematicEditor {
+    schematic: Schematic,
+    config: SchematicConfig,
+    part_sum: BigUint,
+    gear_ratio_sum: BigUint,
+}
+
+/// The numbers and gears whose contributions an edit at one position can change.
+struct Affected {
+    numbers: Vec<u32>,
+    gears: Vec<SchematicPosition>,
+}
+
+/// The numbers in the edited row that an edit replaces, and the numbers that replace them.
+struct RowEdit {
+    removed: Vec<u32>,
+    added: Vec<SchematicNumber>,
+}
+
+/// Adds `value` to `total`. `BigUint` arithmetic never overflows.
+fn add(total: &mut BigUint, value: BigUint) {
+    *total = std::mem::take(total).try_add(value).unwrap();
+}
+
+/// Takes `value`, which was added to `total` earlier, back out of it.
+fn subtract(total: &mut BigUint, value: BigUint) {
+    *total = total
+        .checked_sub(&value)
+        .expect("only contributions that were added are taken out");
+}
+
+impl SchematicEditor {
+    /// Edits join and split numbers a digit at a time, so the schematic is always read a
+    /// character at a time and `config.tokens` is ignored.
+    pub fn new(input: &mut File, config: &SchematicConfig) -> Result<Self, SchematicError> {
+        let config = SchematicConfig {
+            tokens: None,
+            ..config.clone()
+        };
+        let puzzle_input: PuzzleInput<String> = PuzzleInput::rows_from_file(input)?;
+        let schematic = Schematic::parse(&puzzle_input, &config)?;
+        let mut editor = Self {
+            schematic,
+            config,
+            part_sum: BigUint::default(),
+            gear_ratio_sum: BigUint::default(),
+        };
+        for id in 0..editor.schematic.numbers.len() as u32 {
+            let contribution = editor.part_contribution(id);
+            add(&mut editor.part_sum, contribution);
+        }
+        for index in 0..editor.schematic.cells.len() {
+            let pos = editor.position(index);
+            let contribution = editor.gear_contribution(pos);
+            add(&mut editor.gear_ratio_sum, contribution);
+        }
+        Ok(editor)
+    }
+
+    pub fn part_sum<A: Answer>(&self) -> Result<A, OverflowError> {
+        self.part_sum.to_answer()
+    }
+
+    pub fn gear_ratio_sum<A: Answer>(&self) -> Result<A, OverflowError> {
+        self.gear_ratio_sum.to_answer()
+    }
+
+    pub fn width(&self) -> usize {
+        self.schematic.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.schematic.height
+    }
+
+    /// Writes `digit` at `row`, `col`, joining it onto any numbers either side of it.
+    pub fn set_digit(
+        &mut self,
+        row: usize,
+        col: usize,
+        digit: u8,
+    ) -> Result<(), EditSchematicError> {
+        if digit >= 10 {
+            return Err(EditSchematicError::NotADigit(digit));
+        }
+        // The digit always ends up in one of the numbers the edit adds, which overwrites this cell.
+        self.edit(
+            row,
+            col,
+            Some(b'0' + digit),
+            Cell::Blank(self.config.blank()),
+        )
+    }
+
+    /// Blanks the cell at `row`, `col`, splitting any number it was part of.
+    pub fn clear(&mut self, row: usize, col: usize) -> Result<(), EditSchematicError> {
+        self.edit(row, col, None, Cell::Blank(self.config.blank()))
+    }
+
+    /// Writes `symbol` at `row`, `col`, classified by the editor's [`SchematicConfig`] the same
+    /// way as when parsing, so a blank character clears the cell and a gear character places a
+    /// gear.
+    pub fn place_symbol(
+        &mut self,
+        row: usize,
+        col: usize,
+        symbol: char,
+    ) -> Result<(), EditSchematicError> {
+        let cell = match SchematicParserState::classify(symbol, &self.config) {
+            SchematicParserState::Number => return Err(EditSchematicError::DigitSymbol(symbol)),
+            SchematicParserState::Dot => Cell::Blank(symbol),
+            SchematicParserState::Symbol => Cell::Symbol(symbol),
+            SchematicParserState::Gear => Cell::Gear(symbol),
+        };
+        self.edit(row, col, None, cell)
+    }
+
+    /// Draws the schematic as it currently stands.
+    pub fn render(&self, options: &RenderOptions) -> String {
+        self.schematic.render(options)
+    }
+
+    fn position(&self, index: usize) -> SchematicPosition {
+        SchematicPosition::new(
+            (index / self.schematic.width) as isize,
+            (index % self.schematic.width) as isize,
+        )
+    }
+
+    fn index(&self, pos: SchematicPosition) -> usize {
+        pos.row as usize * self.schematic.width + pos.col as usize
+    }
+
+    fn part_contribution(&self, id: u32) -> BigUint {
+        let number = &self.schematic.numbers[id as usize];
+        if self.schematic.is_part_number(number) {
+            BigUint::from_u64(number.value)
+        } else {
+            BigUint::default()
+        }
+    }
+
+    fn gear_contribution(&self, pos: SchematicPosition) -> BigUint {
+        if !matches!(self.schematic.cell(pos), Cell::Gear(_)) {
+            return BigUint::default();
+        }
+        let ids = self.schematic.gear_numbers(pos);
+        if !self.schematic.gear_adjacency.matches(ids.len()) {
+            return BigUint::default();
+        }
+        let values = ids
+            .into_iter()
+            .map(|id| BigUint::from_u64(self.schematic.numbers[id as usize].value));
+        BigUint::try_product(values).unwrap()
+    }
+
+    /// Every number with a cell in the neighbourhood of `pos`, at `pos` or either side of it in
+    /// its row, and every gear in the neighbourhood of one of those numbers or at or around `pos`.
+    fn affected(&self, pos: SchematicPosition) -> Affected {
+        let row_neighbours =
+            [pos.col - 1, pos.col, pos.col + 1].map(|col| SchematicPosition::new(pos.row, col));
+        let around = self
+            .schematic
+            .neighbours(&SchematicEntry::Gear { pos })
+            .chain(row_neighbours);
+        let mut numbers = vec![];
+        let mut gears = vec![];
+        for cursor in around {
+            match self.schematic.cell(cursor) {
+                Cell::Number(id) if !numbers.contains(&id) => numbers.push(id),
+                Cell::Gear(_) if !gears.contains(&cursor) => gears.push(cursor),
+                _ => {}
+            }
+        }
+        for &id in numbers.iter() {
+            for cursor in self
+                .schematic
+                .neighbours(&SchematicEntry::from(&self.schematic.numbers[id as usize]))
+            {
+                if matches!(self.schematic.cell(cursor), Cell::Gear(_)) && !gears.contains(&cursor)
+                {
+                    gears.push(cursor);
+                }
+            }
+        }
+        Affected { numbers, gears }
+    }
+
+    fn edit(
+        &mut self,
+        row: usize,
+        col: usize,
+        digit: Option<u8>,
+        cell: Cell,
+    ) -> Result<(), EditSchematicError> {
+        if row >= self.schematic.height || col >= self.schematic.width {
+            return Err(EditSchematicError::OutOfBounds { row, col });
+        }
+        let pos = SchematicPosition::new(row as isize, col as isize);
+
+        // Work out the new numbers before changing anything, so a rejected edit changes nothing.
+        let before = self.affected(pos);
+        let row_edit = self.split_row(pos, &before.numbers, digit)?;
+
+        // Take out everything the edit could change, make the edit, then add it all back.
+        for &id in before.numbers.iter() {
+            let contribution = self.part_contribution(id);
+            subtract(&mut self.part_sum, contribution);
+        }
+        for &gear in before.gears.iter() {
+            let contribution = self.gear_contribution(gear);
+            subtract(&mut self.gear_ratio_sum, contribution);
+        }
+
+        self.rewrite_row(pos, row_edit, cell);
+
+        // Any gear whose neighbours changed was next to an old number or to `pos`, so it is
+        // already in `before.gears`; only the cell at `pos` itself may have become a gear.
+        let after = self.affected(pos);
+        for &id in after.numbers.iter() {
+            let contribution = self.part_contribution(id);
+            add(&mut self.part_sum, contribution);
+        }
+        let mut gears = before.gears;
+        if !gears.contains(&pos) {
+            gears.push(pos);
+        }
+        for gear in gears {
+            let contribution = self.gear_contribution(gear);
+            add(&mut self.gear_ratio_sum, contribution);
+        }
+        Ok(())
+    }
+
+    /// Re-splits the digits around `pos` in its row into numbers as they will be once `digit` is
+    /// written there, merging numbers that a new digit joins and splitting one that loses a digit.
+    fn split_row(
+        &self,
+        pos: SchematicPosition,
+        nearby: &[u32],
+        digit: Option<u8>,
+    ) -> Result<RowEdit, ParseSchematicError> {
+        let in_row = nearby
+            .iter()
+            .copied()
+            .filter(|&id| self.schematic.numbers[id as usize].first_pos.row == pos.row)
+            .collect::<Vec<u32>>();
+        let first_col = in_row
+            .iter()
+            .map(|&id| self.schematic.numbers[id as usize].first_pos.col)
+            .fold(pos.col, isize::min);
+        let last_col = in_row
+            .iter()
+            .map(|&id| self.schematic.numbers[id as usize].last_pos.col)
+            .fold(pos.col, isize::max);
+
+        // The numbers next to `pos` in its row, together with `pos`, cover `first_col..=last_col`
+        // without gaps.
+        let mut digits = vec![None; (last_col - first_col + 1) as usize];
+        for &id in in_row.iter() {
+            let number = &self.schematic.numbers[id as usize];
+            for (offset, digit) in number.digits().bytes().enumerate() {
+                digits[(number.first_pos.col - first_col) as usize + offset] = Some(digit);
+            }
+        }
+        digits[(pos.col - first_col) as usize] = digit;
+
+        let mut added = vec![];
+        let mut start = 0;
+        while start < digits.len() {
+            if digits[start].is_none() {
+                start += 1;
+                continue;
+            }
+            let end = (start..digits.len())
+                .find(|&i| digits[i].is_none())
+                .unwrap_or(digits.len());
+            let run = digits[start..end]
+                .iter()
+                .map(|d| d.unwrap() as char)
+                .collect::<String>();
+            let first_pos = SchematicPosition::new(pos.row, first_col + start as isize);
+            added.push(SchematicNumber {
+                value: parse_number(&run, first_pos.row as usize, first_pos.col as usize)?,
+                first_pos,
+                last_pos: SchematicPosition::new(pos.row, first_col + end as isize - 1),
+            });
+            start = end;
+        }
+        Ok(RowEdit {
+            removed: in_row,
+            added,
+        })
+    }
+
+    /// Writes `cell` at `pos` and swaps the numbers of its row for those worked out by
+    /// [`SchematicEditor::split_row`].
+    fn rewrite_row(&mut self, pos: SchematicPosition, row_edit: RowEdit, cell: Cell) {
+        let mut removed = row_edit.removed;
+        removed.sort_unstable();
+        for id in removed.into_iter().rev() {
+            self.remove_number(id);
+        }
+        let index = self.index(pos);
+        self.schematic.cells[index] = cell;
+        for number in row_edit.added {
+            self.add_number(number);
+        }
+    }
+
+    /// Removes number `id`, moving the last number into its slot. Its cells are left as they are
+    /// for the caller to overwrite.
+    fn remove_number(&mut self, id: u32) {
+        self.schematic.numbers.swap_remove(id as usize);
+        if let Some(moved) = self.schematic.numbers.get(id as usize).copied() {
+            let start = self.index(moved.first_pos);
+            self.schematic.cells[start..start + moved.width()].fill(Cell::Number(id));
+        }
+    }
+
+    fn add_number(&mut self, number: SchematicNumber) {
+        let id =
+            u32::try_from(self.schematic.numbers.len()).expect("Too many numbers in schematic");
+        let start = self.index(number.first_pos);
+        self.schematic.cells[start..start + number.width()].fill(Cell::Number(id));
+        self.schematic.numbers.push(number);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::answer::{BigUint, OverflowError};
+    use crate::common::grid::Edges;
+    use crate::common::puzzle_input_reader::test::MockFile;
+    use crate::day3::{
+        run_part_1_with_config, run_part_2_with_config, GearAdjacency, ParseSchematicError,
+        ParseSchematicErrorKind, RenderOptions, SchematicConfig,
+    };
+
+    use super::{EditSchematicError, SchematicEditor};
+
+    const SAMPLE: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    fn editor(contents: &str) -> SchematicEditor {
+        editor_with_config(contents, &SchematicConfig::default())
+    }
+
+    fn editor_with_config(contents: &str, config: &SchematicConfig) -> SchematicEditor {
+        let mut input = MockFile::with_contents(contents);
+        SchematicEditor::new(input.get_file(), config).unwrap()
+    }
+
+    fn text(editor: &SchematicEditor) -> String {
+        editor.render(&RenderOptions {
+            colour: false,
+            ..RenderOptions::default()
+        })
+    }
+
+    /// Checks the editor's running sums against solving its current grid from scratch.
+    fn assert_matches_recompute(editor: &SchematicEditor) {
+        let text = text(editor);
+        let mut input = MockFile::with_contents(&text);
+        let part_sum = run_part_1_with_config::<BigUint>(input.get_file(), &editor.config).unwrap();
+        let mut input = MockFile::with_contents(&text);
+        let gear_ratio_sum =
+            run_part_2_with_config::<BigUint>(input.get_file(), &editor.config).unwrap();
+        assert_eq!(
+            (
+                editor.part_sum::<BigUint>().unwrap().to_string(),
+                editor.gear_ratio_sum::<BigUint>().unwrap().to_string()
+            ),
+            (part_sum.to_string(), gear_ratio_sum.to_string()),
+            "after editing to\n{text}"
+        );
+    }
+
+    #[test]
+    fn test_sample_input() {
+        let editor = editor(SAMPLE);
+        assert_eq!(editor.part_sum::<u64>(), Ok(4361));
+        assert_eq!(editor.gear_ratio_sum::<u64>(), Ok(467835));
+    }
+
+    #[test]
+    fn test_merge_and_split() {
+        let mut editor = editor(SAMPLE);
+        // Joining 467 and 114 makes one number, 46701114, next to the gear below it.
+        editor.set_digit(0, 3, 0).unwrap();
+        editor.set_digit(0, 4, 1).unwrap();
+        assert_eq!(editor.part_sum::<u64>(), Ok(4361 - 467 + 46701114));
+        assert_eq!(
+            editor.gear_ratio_sum::<u64>(),
+            Ok(467835 - 467 * 35 + 46701114 * 35)
+        );
+        assert_matches_recompute(&editor);
+
+        // Splitting it again leaves 467 and 1114, which gives the gear three numbers.
+        editor.clear(0, 3).unwrap();
+        assert_eq!(editor.part_sum::<u64>(), Ok(4361 + 1114));
+        assert_eq!(editor.gear_ratio_sum::<u64>(), Ok(467835 - 467 * 35));
+        assert_matches_recompute(&editor);
+        editor.clear(0, 4).unwrap();
+        assert_eq!(editor.part_sum::<u64>(), Ok(4361));
+        assert_eq!(editor.gear_ratio_sum::<u64>(), Ok(467835));
+    }
+
+    #[test]
+    fn test_symbols() {
+        let mut editor = editor(SAMPLE);
+        // 58 becomes a part number, and the new gear sits between it and 755.
+        editor.place_symbol(6, 7, '*').unwrap();
+        assert_eq!(editor.part_sum::<u64>(), Ok(4361 + 58));
+        assert_eq!(editor.gear_ratio_sum::<u64>(), Ok(467835 + 58 * 755));
+        assert_matches_recompute(&editor);
+        // A blank character clears the symbol again.
+        editor.place_symbol(6, 7, '.').unwrap();
+        assert_eq!(editor.part_sum::<u64>(), Ok(4361));
+        assert_eq!(editor.gear_ratio_sum::<u64>(), Ok(467835));
+        // Overwriting a digit with a symbol splits its number.
+        editor.place_symbol(2, 7, '#').unwrap();
+        assert_matches_recompute(&editor);
+    }
+
+    #[test]
+    fn test_edges() {
+        let torus = SchematicConfig {
+            edges: Edges::Torus,
+            ..Default::default()
+        };
+        // On a torus the corner gear touches the 1 and 2 in the opposite corners.
+        let mut editor = editor_with_config("....1\n.....\n2....", &torus);
+        editor.place_symbol(0, 0, '*').unwrap();
+        assert_eq!(editor.part_sum::<u64>(), Ok(1 + 2));
+        assert_eq!(editor.gear_ratio_sum::<u64>(), Ok(2));
+        assert_matches_recompute(&editor);
+        editor.set_digit(1, 1, 5).unwrap();
+        assert_eq!(editor.part_sum::<u64>(), Ok(1 + 2 + 5));
+        assert_eq!(editor.gear_ratio_sum::<u64>(), Ok(0));
+        assert_matches_recompute(&editor);
+        // In a single column each number is only counted once, however often the torus brings it
+        // back into reach.
+        let mut editor = editor_with_config("3\n.\n4", &torus);
+        editor.place_symbol(1, 0, '*').unwrap();
+        assert_eq!(editor.gear_ratio_sum::<u64>(), Ok(3 * 4));
+        assert_matches_recompute(&editor);
+
+        let mirrored = SchematicConfig {
+            edges: Edges::Mirrored,
+            ..Default::default()
+        };
+        // The gear sees the 2 beside it and the image of the 2 in the mirror above.
+        let mut editor = editor_with_config("2....\n.....\n.....", &mirrored);
+        editor.place_symbol(0, 1, '*').unwrap();
+        assert_eq!(editor.part_sum::<u64>(), Ok(2));
+        assert_eq!(editor.gear_ratio_sum::<u64>(), Ok(2 * 2));
+        assert_matches_recompute(&editor);
+        editor.set_digit(1, 2, 7).unwrap();
+        assert_eq!(editor.part_sum::<u64>(), Ok(2 + 7));
+        assert_eq!(editor.gear_ratio_sum::<u64>(), Ok(0));
+        assert_matches_recompute(&editor);
+        editor.clear(0, 0).unwrap();
+        assert_eq!(editor.part_sum::<u64>(), Ok(7));
+        assert_eq!(editor.gear_ratio_sum::<u64>(), Ok(0));
+        assert_matches_recompute(&editor);
+        // In a single column the mirrors either side show each number three times.
+        let config = SchematicConfig {
+            gear_adjacency: GearAdjacency::Exactly(3),
+            ..mirrored
+        };
+        let mut editor = editor_with_config("3\n.\n4", &config);
+        editor.place_symbol(1, 0, '*').unwrap();
+        assert_eq!(editor.gear_ratio_sum::<u64>(), Ok(0));
+        assert_matches_recompute(&editor);
+        editor.clear(2, 0).unwrap();
+        assert_eq!(editor.gear_ratio_sum::<u64>(), Ok(3 * 3 * 3));
+        assert_matches_recompute(&editor);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut editor = editor("9999999999*9999999999");
+        assert_eq!(
+            editor.gear_ratio_sum::<u64>(),
+            Err(OverflowError { type_name: "u64" })
+        );
+        assert_eq!(
+            editor.gear_ratio_sum::<BigUint>().unwrap().to_string(),
+            "99999999980000000001"
+        );
+        // Shrinking one number brings the ratio back within a u64.
+        editor.clear(0, 0).unwrap();
+        assert_eq!(editor.gear_ratio_sum::<u64>(), Ok(999999999 * 9999999999));
+        assert_eq!(editor.part_sum::<u64>(), Ok(999999999 + 9999999999));
+    }
+
+    #[test]
+    fn test_rejected_edits() {
+        let mut editor = editor("1111111111.1111111111\n.....................");
+        let before = text(&editor);
+        // Joining the two numbers would make one with 21 digits.
+        assert_eq!(
+            editor.set_digit(0, 10, 1),
+            Err(EditSchematicError::Parse(ParseSchematicError {
+                row: 0,
+                col: 0,
+                kind: ParseSchematicErrorKind::NumberTooLarge(String::from(
+                    "111111111111111111111"
+                )),
+            }))
+        );
+        assert_eq!(
+            editor.place_symbol(1, 3, '7'),
+            Err(EditSchematicError::DigitSymbol('7'))
+        );
+        assert_eq!(
+            editor.set_digit(1, 3, 10),
+            Err(EditSchematicError::NotADigit(10))
+        );
+        assert_eq!(
+            editor.clear(2, 0),
+            Err(EditSchematicError::OutOfBounds { row: 2, col: 0 })
+        );
+        assert_eq!(text(&editor), before);
+        assert_eq!(editor.part_sum::<u64>(), Ok(0));
+        // The row is still editable after a rejected edit.
+        editor.place_symbol(1, 10, '#').unwrap();
+        assert_eq!(editor.part_sum::<u64>(), Ok(2222222222));
+        assert_matches_recompute(&editor);
+    }
+
+    #[test]
+    fn test_random_edits_match_recompute() {
+        let mut editor = editor(SAMPLE);
+        // A small xorshift generator so the edit sequence is the same on every run.
+        let mut state: u32 = 0x2545_f491;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as usize
+        };
+        for _ in 0..500 {
+            let (row, col) = (next() % editor.height(), next() % editor.width());
+            match next() % 4 {
+                0 | 1 => editor.set_digit(row, col, (next() % 10) as u8),
+                2 => editor.clear(row, col),
+                _ => editor.place_symbol(row, col, ['*', '#', '+'][next() % 3]),
+            }
+            .unwrap();
+            assert_matches_recompute(&editor);
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+use std::fs::File;
+
+use crate::common::json::{json_array, json_string};
+use crate::common::puzzle_input_reader::PuzzleInput;
+
+use super::{Cell, Schematic, SchematicConfig, SchematicEntry, SchematicError};
+
+/// A number in the schematic, spanning `first_col..=last_col` of `row`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberNode {
+    pub value: u64,
+    pub row: usize,
+    pub first_col: usize,
+    pub last_col: usize,
+}
+
+/// A symbol in the schematic. `gear` is set for gear characters whether or not they have the
+/// right number of neighbours to count as a gear.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolNode {
+    pub character: char,
+    pub row: usize,
+    pub col: usize,
+    pub gear: bool,
+}
+
+/// The bipartite graph of which numbers touch which symbols.
+///
+/// Numbers and symbols are both in row-major order, and edges are `(number, symbol)` index pairs
+/// sorted the same way, so the graph and its exports are deterministic. The edges are also kept
+/// as a sorted list of neighbours for each node, so that neighbour and isolation queries do not
+/// have to scan every edge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdjacencyGraph {
+    numbers: Vec<NumberNode>,
+    symbols: Vec<SymbolNode>,
+    edges: Vec<(usize, usize)>,
+    number_neighbours: Vec<Vec<usize>>,
+    symbol_neighbours: Vec<Vec<usize>>,
+}
+
+fn dot_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl AdjacencyGraph {
+    pub fn numbers(&self) -> &[NumberNode] {
+        &self.numbers
+    }
+
+    pub fn symbols(&self) -> &[SymbolNode] {
+        &self.symbols
+    }
+
+    pub fn edges(&self) -> &[(usize, usize)] {
+        &self.edges
+    }
+
+    /// Indices of the symbols next to number `number`.
+    pub fn symbols_touching(&self, number: usize) -> Vec<usize> {
+        self.number_neighbours[number].clone()
+    }
+
+    /// Indices of the numbers next to symbol `symbol`.
+    pub fn numbers_touching(&self, symbol: usize) -> Vec<usize> {
+        self.symbol_neighbours[symbol].clone()
+    }
+
+    /// Indices of the numbers next to any symbol written as `character`.
+    pub fn numbers_touching_character(&self, character: char) -> Vec<usize> {
+        let mut numbers = self
+            .symbols
+            .iter()
+            .zip(self.symbol_neighbours.iter())
+            .filter(|(symbol, _)| symbol.character == character)
+            .flat_map(|(_, numbers)| numbers.iter().copied())
+            .collect::<Vec<usize>>();
+        numbers.sort_unstable();
+        numbers.dedup();
+        numbers
+    }
+
+    /// Indices of the symbols with no number next to them.
+    pub fn isolated_symbols(&self) -> Vec<usize> {
+        (0..self.symbols.len())
+            .filter(|&symbol| self.symbol_neighbours[symbol].is_empty())
+            .collect()
+    }
+
+    /// Indices of the numbers with no symbol next to them.
+    pub fn isolated_numbers(&self) -> Vec<usize> {
+        (0..self.numbers.len())
+            .filter(|&number| self.number_neighbours[number].is_empty())
+            .collect()
+    }
+
+    /// The graph in Graphviz DOT, with numbers as ellipses `n<i>` and symbols as boxes `s<i>`
+    /// (double circles for gear characters).
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("graph schematic {\n");
+        for (i, number) in self.numbers.iter().enumerate() {
+            dot += &format!(
+                "  n{i} [label={}, shape=ellipse];\n",
+                dot_string(&number.value.to_string())
+            );
+        }
+        for (i, symbol) in self.symbols.iter().enumerate() {
+            let shape = if symbol.gear { "doublecircle" } else { "box" };
+            dot += &format!(
+                "  s{i} [label={}, shape={shape}];\n",
+                dot_string(&symbol.character.to_string())
+            );
+        }
+        for (number, symbol) in self.edges.iter() {
+            dot += &format!("  n{number} -- s{symbol};\n");
+        }
+        dot += "}\n";
+        dot
+    }
+
+    pub fn to_json(&self) -> String {
+        let numbers = json_array(self.numbers.iter().map(|number| {
+            format!(
+                "{{\"value\":{},\"row\":{},\"first_col\":{},\"last_col\":{}}}",
+                number.value, number.row, number.first_col, number.last_col
+            )
+        }));
+        let symbols = json_array(self.symbols.iter().map(|symbol| {
+            format!(
+                "{{\"character\":{},\"row\":{},\"col\":{},\"gear\":{}}}",
+                json_string(&symbol.character.to_string()),
+                symbol.row,
+                symbol.col,
+                symbol.gear
+            )
+        }));
+        let edges = json_array(
+            self.edges
+                .iter()
+                .map(|(number, symbol)| format!("[{number},{symbol}]")),
+        );
+        format!("{{\"numbers\":{numbers},\"symbols\":{symbols},\"edges\":{edges}}}")
+    }
+}
+
+impl Schematic {
+    pub(super) fn graph(&self) -> AdjacencyGraph {
+        let mut symbols = vec![];
+        let mut symbol_ids = HashMap::new();
+        for (index, cell) in self.cells.iter().enumerate() {
+            let (character, gear) = match *cell {
+                Cell::Symbol(character) => (character, false),
+                Cell::Gear(character) => (character, true),
+                _ => continue,
+            };
+            symbol_ids.insert(index, symbols.len());
+            symbols.push(SymbolNode {
+                character,
+                row: index / self.width,
+                col: index % self.width,
+                gear,
+            });
+        }
+
+        let mut edges = vec![];
+        let mut number_neighbours = vec![];
+        let mut symbol_neighbours = vec![vec![]; symbols.len()];
+        for (id, number) in self.numbers.iter().enumerate() {
+            let mut touching = self
+                .neighbours(&SchematicEntry::from(number))
+                .filter(|pos| matches!(self.cell(*pos), Cell::Symbol(_) | Cell::Gear(_)))
+                .map(|pos| symbol_ids[&(pos.row as usize * self.width + pos.col as usize)])
+                .collect::<Vec<usize>>();
+            touching.sort_unstable();
+            touching.dedup();
+            for &symbol in touching.iter() {
+                edges.push((id, symbol));
+                // Numbers are visited in order, so each symbol's list stays sorted.
+                symbol_neighbours[symbol].push(id);
+            }
+            number_neighbours.push(touching);
+        }
+
+        let numbers = self.numbers.iter().map(NumberNode::from).collect();
+        AdjacencyGraph {
+            numbers,
+            symbols,
+            edges,
+            number_neighbours,
+            symbol_neighbours,
+        }
+    }
+}
+
+/// Builds the number/symbol adjacency graph of the schematic in `input`.
+pub fn build_graph(
+    input: &mut File,
+    config: &SchematicConfig,
+) -> Result<AdjacencyGraph, SchematicError> {
+    let puzzle_input: PuzzleInput<String> = PuzzleInput::rows_from_file(input)?;
+    Ok(Schematic::parse(&puzzle_input, config)?.graph())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::puzzle_input_reader::test::MockFile;
+    use crate::day3::SchematicConfig;
+
+    use super::{build_graph, AdjacencyGraph, SymbolNode};
+
+    fn graph(contents: &str) -> AdjacencyGraph {
+        let mut input = MockFile::with_contents(contents);
+        build_graph(input.get_file(), &SchematicConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_sample_graph() {
+        let graph = graph(
+            "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..",
+        );
+        assert_eq!(graph.numbers().len(), 10);
+        assert_eq!(graph.symbols().len(), 6);
+        // Every part number touches exactly one symbol in the sample.
+        assert_eq!(graph.edges().len(), 8);
+        assert_eq!(graph.isolated_numbers(), vec![1, 5]);
+        assert!(graph.isolated_symbols().is_empty());
+        let values = |numbers: Vec<usize>| {
+            numbers
+                .into_iter()
+                .map(|n| graph.numbers()[n].value)
+                .collect::<Vec<u64>>()
+        };
+        assert_eq!(values(graph.numbers_touching(0)), vec![467, 35]);
+        assert_eq!(
+            values(graph.numbers_touching_character('*')),
+            vec![467, 35, 617, 755, 598]
+        );
+        assert_eq!(graph.symbols_touching(4), vec![2]);
+        assert_eq!(
+            graph.symbols()[2],
+            SymbolNode {
+                character: '*',
+                row: 4,
+                col: 3,
+                gear: true
+            }
+        );
+    }
+
+    #[test]
+    fn test_isolated_symbols() {
+        let graph = graph("#.1\n..&\n@..");
+        assert_eq!(graph.isolated_symbols(), vec![0, 2]);
+        assert_eq!(graph.numbers_touching(1), vec![0]);
+        assert_eq!(graph.symbols_touching(0), vec![1]);
+    }
+
+    #[test]
+    fn test_exports() {
+        let graph = graph("12*\n...\n..\"");
+        assert_eq!(
+            graph.to_dot(),
+            "graph schematic {
+  n0 [label=\"12\", shape=ellipse];
+  s0 [label=\"*\", shape=doublecircle];
+  s1 [label=\"\\\"\", shape=box];
+  n0 -- s0;
+}
+"
+        );
+        assert_eq!(
+            graph.to_json(),
+            "{\"numbers\":[{\"value\":12,\"row\":0,\"first_col\":0,\"last_col\":1}],\
+             \"symbols\":[{\"character\":\"*\",\"row\":0,\"col\":2,\"gear\":true},\
+             {\"character\":\"\\\"\",\"row\":2,\"col\":2,\"gear\":false}],\
+             \"edges\":[[0,0]]}"
+        );
+    }
+}
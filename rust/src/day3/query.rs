@@ -0,0 +1,342 @@
+use std::fs::File;
+use std::ops::Range;
+
+use crate::common::answer::{Answer, OverflowError};
+use crate::common::puzzle_input_reader::PuzzleInput;
+
+use super::{
+    Cell, NumberNode, Schematic, SchematicConfig, SchematicEntry, SchematicError, SchematicNumber,
+    SchematicPosition, SymbolNode,
+};
+
+/// Whatever occupies a cell of the schematic, as returned by [`Schematic::entry_at`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchematicItem {
+    Number(NumberNode),
+    Symbol(SymbolNode),
+}
+
+/// A gear that satisfies the gear adjacency rule, with the numbers around it in row-major order
+/// of their first digit. A number the gear also sees in a mirror is listed once for each image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gear {
+    pub row: usize,
+    pub col: usize,
+    pub numbers: Vec<NumberNode>,
+}
+
+impl Gear {
+    pub fn ratio<A: Answer>(&self) -> Result<A, OverflowError> {
+        A::try_product(self.numbers.iter().map(|number| A::from_u64(number.value)))
+    }
+}
+
+impl From<&SchematicNumber> for NumberNode {
+    fn from(number: &SchematicNumber) -> Self {
+        NumberNode {
+            value: number.value,
+            row: number.first_pos.row as usize,
+            first_col: number.first_pos.col as usize,
+            last_col: number.last_pos.col as usize,
+        }
+    }
+}
+
+/// Every query returns its results in row-major order: numbers by their first digit, symbols and
+/// gears by their own cell.
+impl Schematic {
+    pub fn from_file(input: &mut File, config: &SchematicConfig) -> Result<Self, SchematicError> {
+        let puzzle_input: PuzzleInput<String> = PuzzleInput::rows_from_file(input)?;
+        Ok(Self::parse(&puzzle_input, config)?)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn symbol_at(&self, pos: SchematicPosition) -> Option<SymbolNode> {
+        let (character, gear) = match self.cell(pos) {
+            Cell::Symbol(character) => (character, false),
+            Cell::Gear(character) => (character, true),
+            _ => return None,
+        };
+        Some(SymbolNode {
+            character,
+            row: pos.row as usize,
+            col: pos.col as usize,
+            gear,
+        })
+    }
+
+    /// The number or symbol covering `row`, `col`, if any.
+    pub fn entry_at(&self, row: usize, col: usize) -> Option<SchematicItem> {
+        let pos = SchematicPosition::new(row as isize, col as isize);
+        match self.cell(pos) {
+            Cell::Number(id) => Some(SchematicItem::Number(NumberNode::from(
+                &self.numbers[id as usize],
+            ))),
+            _ => self.symbol_at(pos).map(SchematicItem::Symbol),
+        }
+    }
+
+    /// Every number with at least one digit inside the rectangle of `rows` and `cols`.
+    pub fn numbers_in(&self, rows: Range<usize>, cols: Range<usize>) -> Vec<NumberNode> {
+        let mut numbers = self
+            .numbers
+            .iter()
+            .filter(|number| {
+                let row = number.first_pos.row as usize;
+                rows.contains(&row)
+                    && (number.first_pos.col as usize) < cols.end
+                    && number.last_pos.col as usize >= cols.start
+            })
+            .map(NumberNode::from)
+            .collect::<Vec<NumberNode>>();
+        sort_numbers(&mut numbers);
+        numbers
+    }
+
+    /// Every number next to `row`, `col` under the schematic's neighbourhood, not counting a
+    /// number covering the position itself.
+    pub fn numbers_adjacent_to(&self, row: usize, col: usize) -> Vec<NumberNode> {
+        let pos = SchematicPosition::new(row as isize, col as isize);
+        let mut ids = vec![];
+        for cursor in self.neighbours(&SchematicEntry::Symbol { pos }) {
+            if let Cell::Number(id) = self.cell(cursor) {
+                if !ids.contains(&id) && self.cell(pos) != Cell::Number(id) {
+                    ids.push(id);
+                }
+            }
+        }
+        let mut numbers = ids
+            .into_iter()
+            .map(|id| NumberNode::from(&self.numbers[id as usize]))
+            .collect::<Vec<NumberNode>>();
+        sort_numbers(&mut numbers);
+        numbers
+    }
+
+    /// The symbol closest to `number`, in the fewest steps from one cell to a cell in its
+    /// neighbourhood, so that under [`NeighbourhoodShape::VonNeumann`] a diagonal symbol is still
+    /// found, two steps away. Of several symbols equally close, the first in row-major order wins.
+    ///
+    /// The search visits each cell of the schematic at most once.
+    ///
+    /// [`NeighbourhoodShape::VonNeumann`]: crate::common::grid::NeighbourhoodShape::VonNeumann
+    pub fn nearest_symbol(&self, number: &NumberNode) -> Option<SymbolNode> {
+        let index = |pos: SchematicPosition| pos.row as usize * self.width + pos.col as usize;
+        let mut seen = vec![false; self.cells.len()];
+        let mut frontier = (number.first_col..=number.last_col)
+            .filter(|&col| number.row < self.height && col < self.width)
+            .map(|col| SchematicPosition::new(number.row as isize, col as isize))
+            .collect::<Vec<SchematicPosition>>();
+        for &pos in frontier.iter() {
+            seen[index(pos)] = true;
+        }
+        while !frontier.is_empty() {
+            let mut next = vec![];
+            for pos in frontier {
+                for cursor in self.neighbours(&SchematicEntry::Symbol { pos }) {
+                    if !seen[index(cursor)] {
+                        seen[index(cursor)] = true;
+                        next.push(cursor);
+                    }
+                }
+            }
+            let nearest = next
+                .iter()
+                .filter_map(|&pos| self.symbol_at(pos))
+                .min_by_key(|symbol| (symbol.row, symbol.col));
+            if nearest.is_some() {
+                return nearest;
+            }
+            frontier = next;
+        }
+        None
+    }
+
+    /// Every gear whose numbers satisfy the gear adjacency rule.
+    pub fn gears(&self) -> Vec<Gear> {
+        let mut gears = vec![];
+        for (index, _) in self
+            .cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| matches!(cell, Cell::Gear(_)))
+        {
+            let (row, col) = (index / self.width, index % self.width);
+            let ids = self.gear_numbers(SchematicPosition::new(row as isize, col as isize));
+            if self.gear_adjacency.matches(ids.len()) {
+                let mut numbers = ids
+                    .into_iter()
+                    .map(|id| NumberNode::from(&self.numbers[id as usize]))
+                    .collect::<Vec<NumberNode>>();
+                sort_numbers(&mut numbers);
+                gears.push(Gear { row, col, numbers });
+            }
+        }
+        gears
+    }
+}
+
+fn sort_numbers(numbers: &mut [NumberNode]) {
+    numbers.sort_by_key(|number| (number.row, number.first_col));
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::grid::{Neighbourhood, NeighbourhoodShape};
+    use crate::common::puzzle_input_reader::test::MockFile;
+    use crate::day3::{NumberNode, Schematic, SchematicConfig, SymbolNode};
+
+    use super::SchematicItem;
+
+    const SAMPLE: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    fn schematic(content: &str, config: &SchematicConfig) -> Schematic {
+        let mut input = MockFile::with_contents(content);
+        Schematic::from_file(input.get_file(), config).unwrap()
+    }
+
+    fn number(value: u64, row: usize, first_col: usize) -> NumberNode {
+        NumberNode {
+            value,
+            row,
+            first_col,
+            last_col: first_col + value.to_string().len() - 1,
+        }
+    }
+
+    fn values(numbers: Vec<NumberNode>) -> Vec<u64> {
+        numbers.into_iter().map(|number| number.value).collect()
+    }
+
+    #[test]
+    fn test_entry_at() {
+        let schematic = schematic(SAMPLE, &SchematicConfig::default());
+        assert_eq!(
+            schematic.entry_at(2, 7),
+            Some(SchematicItem::Number(number(633, 2, 6)))
+        );
+        assert_eq!(
+            schematic.entry_at(8, 3),
+            Some(SchematicItem::Symbol(SymbolNode {
+                character: '$',
+                row: 8,
+                col: 3,
+                gear: false
+            }))
+        );
+        assert_eq!(schematic.entry_at(0, 3), None);
+        assert_eq!(schematic.entry_at(100, 100), None);
+    }
+
+    #[test]
+    fn test_numbers_in() {
+        let schematic = schematic(SAMPLE, &SchematicConfig::default());
+        assert_eq!(values(schematic.numbers_in(0..3, 2..4)), vec![467, 35]);
+        assert_eq!(
+            values(schematic.numbers_in(5..10, 7..8)),
+            vec![58, 755, 598]
+        );
+        assert!(schematic.numbers_in(3..4, 0..10).is_empty());
+        assert_eq!(schematic.numbers_in(0..10, 0..10).len(), 10);
+    }
+
+    #[test]
+    fn test_numbers_adjacent_to() {
+        let schematic = schematic(SAMPLE, &SchematicConfig::default());
+        assert_eq!(values(schematic.numbers_adjacent_to(1, 3)), vec![467, 35]);
+        assert_eq!(values(schematic.numbers_adjacent_to(8, 5)), vec![755, 598]);
+        // A number is not adjacent to itself.
+        assert_eq!(values(schematic.numbers_adjacent_to(0, 2)), vec![]);
+        let config = SchematicConfig {
+            neighbourhood: Neighbourhood::new(NeighbourhoodShape::Chebyshev, 2),
+            ..Default::default()
+        };
+        let schematic = self::schematic(SAMPLE, &config);
+        assert_eq!(
+            values(schematic.numbers_adjacent_to(1, 3)),
+            vec![467, 114, 35]
+        );
+    }
+
+    #[test]
+    fn test_nearest_symbol() {
+        let schematic = schematic(SAMPLE, &SchematicConfig::default());
+        let nearest = |value, row, col| {
+            schematic
+                .nearest_symbol(&number(value, row, col))
+                .map(|symbol| (symbol.character, symbol.row, symbol.col))
+        };
+        assert_eq!(nearest(467, 0, 0), Some(('*', 1, 3)));
+        assert_eq!(nearest(114, 0, 5), Some(('*', 1, 3)));
+        assert_eq!(nearest(58, 5, 7), Some(('#', 3, 6)));
+        assert_eq!(nearest(592, 6, 2), Some(('+', 5, 5)));
+        // Equally close symbols resolve to the first in row-major order.
+        let schematic = self::schematic("...\n.1&\n#..", &SchematicConfig::default());
+        assert_eq!(
+            schematic
+                .nearest_symbol(&number(1, 1, 1))
+                .map(|symbol| symbol.character),
+            Some('&')
+        );
+        let schematic = self::schematic("12", &SchematicConfig::default());
+        assert_eq!(schematic.nearest_symbol(&number(12, 0, 0)), None);
+    }
+
+    #[test]
+    fn test_nearest_symbol_von_neumann() {
+        let config = SchematicConfig {
+            neighbourhood: Neighbourhood::new(NeighbourhoodShape::VonNeumann, 1),
+            ..Default::default()
+        };
+        // The symbol is only diagonally reachable, two orthogonal steps away.
+        let schematic = schematic("1.\n.#", &config);
+        assert_eq!(
+            schematic
+                .nearest_symbol(&number(1, 0, 0))
+                .map(|symbol| (symbol.row, symbol.col)),
+            Some((1, 1))
+        );
+        // Both symbols are four steps away, so the first in row-major order wins.
+        let schematic = self::schematic("..#..\n.....\n1...&", &config);
+        assert_eq!(
+            schematic
+                .nearest_symbol(&number(1, 2, 0))
+                .map(|symbol| symbol.character),
+            Some('#')
+        );
+    }
+
+    #[test]
+    fn test_gears() {
+        let schematic = schematic(SAMPLE, &SchematicConfig::default());
+        let gears = schematic.gears();
+        assert_eq!(gears.len(), 2);
+        assert_eq!((gears[0].row, gears[0].col), (1, 3));
+        assert_eq!(gears[0].numbers, vec![number(467, 0, 0), number(35, 2, 2)]);
+        assert_eq!((gears[1].row, gears[1].col), (8, 5));
+        assert_eq!(values(gears[1].numbers.clone()), vec![755, 598]);
+        assert_eq!(
+            gears
+                .iter()
+                .map(|gear| gear.ratio::<u64>().unwrap())
+                .sum::<u64>(),
+            467835
+        );
+    }
+}
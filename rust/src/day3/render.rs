@@ -0,0 +1,233 @@
+use std::fs::File;
+use std::ops::Range;
+
+use colored::{ColoredString, Colorize};
+
+use crate::common::puzzle_input_reader::PuzzleInput;
+
+use super::{Cell, Schematic, SchematicConfig, SchematicError, SchematicPosition};
+
+/// The rows and columns of the schematic to draw. Ranges past the edge of the schematic are
+/// clipped to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderWindow {
+    pub rows: Range<usize>,
+    pub cols: Range<usize>,
+}
+
+/// How to draw a schematic.
+///
+/// With `colour` set, part numbers are green, numbers touching no symbol are red, symbols are
+/// yellow, gears that satisfy the gear adjacency rule are bold cyan and blanks are dimmed.
+/// `colored`'s own overrides, such as `NO_COLOR`, still apply.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderOptions {
+    pub colour: bool,
+    /// Label the columns along the top and the rows down the left, in schematic coordinates.
+    pub rulers: bool,
+    pub window: Option<RenderWindow>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            colour: true,
+            rulers: false,
+            window: None,
+        }
+    }
+}
+
+impl Schematic {
+    fn render_cell(&self, pos: SchematicPosition) -> ColoredString {
+        match self.cell(pos) {
+            Cell::Blank(character) => character.to_string().dimmed(),
+            Cell::Number(id) => {
+                let number = &self.numbers[id as usize];
+                let digits = number.digits();
+                let digit = &digits[(pos.col - number.first_pos.col) as usize..][..1];
+                if self.is_part_number(number) {
+                    digit.green()
+                } else {
+                    digit.red()
+                }
+            }
+            Cell::Symbol(symbol) => symbol.to_string().yellow(),
+            Cell::Gear(gear) if self.is_valid_gear(pos) => gear.to_string().cyan().bold(),
+            Cell::Gear(gear) => gear.to_string().yellow(),
+            Cell::Outside => "".normal(),
+        }
+    }
+
+    pub(super) fn render(&self, options: &RenderOptions) -> String {
+        let (rows, cols) = match &options.window {
+            Some(window) => (
+                window.rows.start.min(self.height)..window.rows.end.min(self.height),
+                window.cols.start.min(self.width)..window.cols.end.min(self.width),
+            ),
+            None => (0..self.height, 0..self.width),
+        };
+        let label_width = rows.end.saturating_sub(1).to_string().len();
+        let paint = |text: ColoredString| {
+            if options.colour {
+                text.to_string()
+            } else {
+                text.clear().to_string()
+            }
+        };
+
+        let mut output = String::new();
+        if options.rulers {
+            let margin = " ".repeat(label_width + 1);
+            if cols.end > 10 {
+                let tens = cols
+                    .clone()
+                    .map(|col| match col % 10 {
+                        0 => char::from_digit((col / 10 % 10) as u32, 10).unwrap(),
+                        _ => ' ',
+                    })
+                    .collect::<String>();
+                output += &format!("{margin}{}\n", paint(tens.trim_end().dimmed()));
+            }
+            let units = cols
+                .clone()
+                .map(|col| char::from_digit((col % 10) as u32, 10).unwrap())
+                .collect::<String>();
+            output += &format!("{margin}{}\n", paint(units.dimmed()));
+        }
+        for row in rows {
+            if options.rulers {
+                output += &paint(format!("{row:>label_width$} ").dimmed());
+            }
+            for col in cols.clone() {
+                output +=
+                    &paint(self.render_cell(SchematicPosition::new(row as isize, col as isize)));
+            }
+            output.push('\n');
+        }
+        output
+    }
+}
+
+/// Draws the schematic in `input` for debugging, as described by `options`. Rows kept at their
+/// own width by [`RaggedRows::Allow`](super::RaggedRows::Allow) are drawn at that width.
+pub fn render_schematic(
+    input: &mut File,
+    config: &SchematicConfig,
+    options: &RenderOptions,
+) -> Result<String, SchematicError> {
+    let puzzle_input: PuzzleInput<String> = PuzzleInput::rows_from_file(input)?;
+    Ok(Schematic::parse(&puzzle_input, config)?.render(options))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::puzzle_input_reader::test::MockFile;
+    use crate::day3::{RaggedRows, SchematicConfig};
+
+    use super::{render_schematic, RenderOptions, RenderWindow};
+
+    const SAMPLE: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    fn plain() -> RenderOptions {
+        RenderOptions {
+            colour: false,
+            ..RenderOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_plain_render() {
+        let mut input = MockFile::with_contents(SAMPLE);
+        let rendered =
+            render_schematic(input.get_file(), &SchematicConfig::default(), &plain()).unwrap();
+        assert_eq!(rendered, format!("{SAMPLE}\n"));
+
+        // Ragged rows are padded with blanks unless kept at their own width, and leading zeros
+        // are kept.
+        let mut input = MockFile::with_contents("007*\n1");
+        let rendered =
+            render_schematic(input.get_file(), &SchematicConfig::default(), &plain()).unwrap();
+        assert_eq!(rendered, "007*\n1...\n");
+        let config = SchematicConfig {
+            ragged_rows: RaggedRows::Allow,
+            ..SchematicConfig::default()
+        };
+        let mut input = MockFile::with_contents("007*\n1");
+        let rendered = render_schematic(input.get_file(), &config, &plain()).unwrap();
+        assert_eq!(rendered, "007*\n1\n");
+    }
+
+    #[test]
+    fn test_rulers_and_window() {
+        let mut input = MockFile::with_contents(SAMPLE);
+        let options = RenderOptions {
+            rulers: true,
+            window: Some(RenderWindow {
+                rows: 8..20,
+                cols: 2..12,
+            }),
+            ..plain()
+        };
+        let rendered =
+            render_schematic(input.get_file(), &SchematicConfig::default(), &options).unwrap();
+        assert_eq!(rendered, "  23456789\n8 .$.*....\n9 64.598..\n");
+
+        let mut input = MockFile::with_contents("....5.......\n....*...1.+.");
+        let options = RenderOptions {
+            rulers: true,
+            ..plain()
+        };
+        let rendered =
+            render_schematic(input.get_file(), &SchematicConfig::default(), &options).unwrap();
+        assert_eq!(
+            rendered,
+            "  0         1\n  012345678901\n0 ....5.......\n1 ....*...1.+.\n"
+        );
+    }
+
+    /// Forces colour on for as long as it is held, so the override does not leak into other
+    /// tests even if an assertion fails.
+    struct ColourOverride;
+
+    impl ColourOverride {
+        fn force() -> Self {
+            colored::control::set_override(true);
+            Self
+        }
+    }
+
+    impl Drop for ColourOverride {
+        fn drop(&mut self) {
+            colored::control::unset_override();
+        }
+    }
+
+    #[test]
+    fn test_colours() {
+        let _colour = ColourOverride::force();
+        let mut input = MockFile::with_contents("1*2.3\n..$..\n.4*..");
+        let rendered = render_schematic(
+            input.get_file(),
+            &SchematicConfig::default(),
+            &RenderOptions::default(),
+        )
+        .unwrap();
+        let lines = rendered.lines().collect::<Vec<&str>>();
+        // A part number, a gear between two numbers and a number touching nothing.
+        assert!(lines[0].starts_with("\x1b[32m1\x1b[0m\x1b[1;36m*\x1b[0m\x1b[32m2\x1b[0m"));
+        assert!(lines[0].ends_with("\x1b[31m3\x1b[0m"));
+        assert!(lines[1].contains("\x1b[33m$\x1b[0m"));
+        // A gear next to a single number is only a symbol.
+        assert!(lines[2].starts_with("\x1b[2m.\x1b[0m\x1b[32m4\x1b[0m\x1b[33m*\x1b[0m"));
+    }
+}
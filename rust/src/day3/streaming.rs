@@ -0,0 +1,451 @@
+use std::io::{BufRead, Lines};
+
+use crate::common::answer::Answer;
+
+use crate::common::grid::{Edges, NeighbourhoodIter};
+
+use super::{
+    Cell, SchematicConfig, SchematicEntryBuilder, SchematicEntryBuilderLine, SchematicError,
+};
+
+/// A part number or gear found while streaming a schematic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchematicEvent {
+    PartNumber(u64),
+    /// A gear and the numbers next to it, from the top left.
+    Gear(Vec<u64>),
+}
+
+struct RowNumber {
+    value: u64,
+    first_col: usize,
+    last_col: usize,
+}
+
+/// One parsed line of the schematic, with number cells indexing into `numbers`.
+#[derive(Default)]
+struct Row {
+    cells: Vec<Cell>,
+    numbers: Vec<RowNumber>,
+}
+
+impl Row {
+    fn cell(&self, col: isize) -> Cell {
+        if col < 0 {
+            return Cell::Outside;
+        }
+        self.cells
+            .get(col as usize)
+            .copied()
+            .unwrap_or(Cell::Outside)
+    }
+}
+
+impl From<SchematicEntryBuilderLine> for Row {
+    fn from(value: SchematicEntryBuilderLine) -> Self {
+        let mut row = Row::default();
+        for builder in value.line {
+            match builder {
+                SchematicEntryBuilder::BlankSpace { width, character } => {
+                    row.cells.extend((0..width).map(|_| Cell::Blank(character)));
+                }
+                SchematicEntryBuilder::Number { value, width } => {
+                    let id = row.numbers.len() as u32;
+                    row.numbers.push(RowNumber {
+                        value,
+                        first_col: row.cells.len(),
+                        last_col: row.cells.len() + width as usize - 1,
+                    });
+                    row.cells.extend((0..width).map(|_| Cell::Number(id)));
+                }
+                SchematicEntryBuilder::Symbol(symbol) => row.cells.push(Cell::Symbol(symbol)),
+                SchematicEntryBuilder::Gear(gear) => row.cells.push(Cell::Gear(gear)),
+            }
+        }
+        row
+    }
+}
+
+/// Solves a schematic one row at a time, holding only the rows within reach of the one being
+/// solved, so memory use depends on the width of the schematic rather than its height.
+///
+/// Each row's part numbers and then gears are yielded, left to right, as soon as the last row
+/// within reach below it has been read. A row that cannot be read or fails to parse is yielded as
+/// an error and ends the stream.
+pub struct SchematicStream<R: BufRead> {
+    lines: Lines<R>,
+    config: SchematicConfig,
+    /// How many rows have been read, and the width of the first, for error reporting and
+    /// [`RaggedRows::Error`](super::RaggedRows::Error).
+    rows_read: usize,
+    first_width: Option<usize>,
+    failed: bool,
+    /// The rows within the neighbourhood radius above and below the one being solved, which is
+    /// in the middle. Slots before the start or after the end of the schematic are `None`.
+    window: Vec<Option<Row>>,
+    events: std::vec::IntoIter<SchematicEvent>,
+}
+
+impl<R: BufRead> SchematicStream<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_config(reader, SchematicConfig::default())
+            .expect("the default config has bounded edges")
+    }
+
+    /// Fails with [`SchematicError::UnboundedEdges`] if `config` has edges other than
+    /// [`Edges::Bounded`], which would need rows from the far end of the schematic.
+    pub fn with_config(reader: R, config: SchematicConfig) -> Result<Self, SchematicError> {
+        if config.edges != Edges::Bounded {
+            return Err(SchematicError::UnboundedEdges(config.edges));
+        }
+        let window = (0..2 * config.neighbourhood.radius + 1)
+            .map(|_| None)
+            .collect();
+        Ok(Self {
+            lines: reader.lines(),
+            config,
+            rows_read: 0,
+            first_width: None,
+            failed: false,
+            window,
+            events: vec![].into_iter(),
+        })
+    }
+
+    fn middle(&self) -> usize {
+        self.config.neighbourhood.radius
+    }
+
+    fn cell(&self, row: isize, col: isize) -> Cell {
+        match usize::try_from(row)
+            .ok()
+            .and_then(|row| self.window.get(row))
+        {
+            Some(Some(r)) => r.cell(col),
+            _ => Cell::Outside,
+        }
+    }
+
+    /// The window positions around the cells `first_col..=last_col` of the middle row.
+    fn neighbours(&self, first_col: usize, last_col: usize) -> NeighbourhoodIter {
+        let middle = self.middle() as isize;
+        self.config
+            .neighbourhood
+            .around((middle, first_col as isize), (middle, last_col as isize))
+    }
+
+    /// Reads the next line into the bottom of the window, or nothing at the end. A blank line is
+    /// a row with no columns, as it is for [`Schematic`](super::Schematic).
+    fn advance(&mut self) -> Result<bool, SchematicError> {
+        let next = match self.lines.next() {
+            Some(line) => Some(self.parse_row(&line?)?),
+            None => None,
+        };
+        let read_row = next.is_some();
+        self.window.rotate_left(1);
+        *self.window.last_mut().unwrap() = next;
+        Ok(read_row)
+    }
+
+    fn parse_row(&mut self, line: &str) -> Result<Row, SchematicError> {
+        let row = self.rows_read;
+        let builder = SchematicEntryBuilderLine::parse(line, row, &self.config)?;
+        let width = builder.width();
+        let expected = *self.first_width.get_or_insert(width);
+        self.config.ragged_rows.check(row, width, expected)?;
+        self.rows_read += 1;
+        Ok(Row::from(builder))
+    }
+
+    fn solve_middle_row(&self) -> Vec<SchematicEvent> {
+        let mut events = vec![];
+        let Some(row) = &self.window[self.middle()] else {
+            return events;
+        };
+        for number in row.numbers.iter() {
+            let is_part = self
+                .neighbours(number.first_col, number.last_col)
+                .any(|(r, c)| matches!(self.cell(r, c), Cell::Symbol(_) | Cell::Gear(_)));
+            if is_part {
+                events.push(SchematicEvent::PartNumber(number.value));
+            }
+        }
+        for (col, _) in row
+            .cells
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| matches!(c, Cell::Gear(_)))
+        {
+            let mut found = vec![];
+            for (r, c) in self.neighbours(col, col) {
+                if let Cell::Number(id) = self.cell(r, c) {
+                    if !found.contains(&(r, id)) {
+                        found.push((r, id));
+                    }
+                }
+            }
+            if self.config.gear_adjacency.matches(found.len()) {
+                events.push(SchematicEvent::Gear(
+                    found
+                        .into_iter()
+                        .map(|(r, id)| {
+                            self.window[r as usize].as_ref().unwrap().numbers[id as usize].value
+                        })
+                        .collect(),
+                ));
+            }
+        }
+        events
+    }
+}
+
+impl<R: BufRead> Iterator for SchematicStream<R> {
+    type Item = Result<SchematicEvent, SchematicError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.events.next() {
+                return Some(Ok(event));
+            }
+            if self.failed {
+                return None;
+            }
+            let read_row = match self.advance() {
+                Ok(read_row) => read_row,
+                Err(error) => {
+                    self.failed = true;
+                    return Some(Err(error));
+                }
+            };
+            // Once the input runs out, keep sliding until the last row read has been solved.
+            if !read_row && self.window[self.middle()..].iter().all(Option::is_none) {
+                return None;
+            }
+            self.events = self.solve_middle_row().into_iter();
+        }
+    }
+}
+
+/// Solves both parts in a single streaming pass, returning the part number sum and the gear ratio
+/// sum.
+pub fn run_streaming<A: Answer>(
+    input: impl BufRead,
+    config: &SchematicConfig,
+) -> Result<(A, A), SchematicError> {
+    let mut part_sum = A::from_u64(0);
+    let mut gear_sum = A::from_u64(0);
+    for event in SchematicStream::with_config(input, config.clone())? {
+        match event? {
+            SchematicEvent::PartNumber(value) => {
+                part_sum = part_sum.try_add(A::from_u64(value))?;
+            }
+            SchematicEvent::Gear(numbers) => {
+                let ratio = A::try_product(numbers.into_iter().map(A::from_u64))?;
+                gear_sum = gear_sum.try_add(ratio)?;
+            }
+        }
+    }
+    Ok((part_sum, gear_sum))
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{BufReader, Cursor, ErrorKind, Read};
+
+    use crate::common::grid::{Edges, Neighbourhood, NeighbourhoodShape};
+    use crate::common::puzzle_input_reader::test::MockFile;
+
+    use super::super::{
+        run_part_1_with_config, run_part_2_with_config, GearAdjacency, ParseSchematicError,
+        ParseSchematicErrorKind, RaggedRows,
+    };
+    use super::{run_streaming, SchematicConfig, SchematicError, SchematicEvent, SchematicStream};
+
+    const SAMPLE: &str = "467..114..\n...*......\n..35..633.\n......#...\n617*......\n.....+.58.\n..592.....\n......755.\n...$.*....\n.664.598..";
+
+    #[test]
+    fn test_sample_input() {
+        let (part_sum, gear_sum) =
+            run_streaming::<u64>(Cursor::new(SAMPLE), &SchematicConfig::default()).unwrap();
+        assert_eq!(part_sum, 4361);
+        assert_eq!(gear_sum, 467835);
+    }
+
+    #[test]
+    fn test_matches_full_solver() {
+        let tests = [
+            SAMPLE,
+            "1.2.3\n4*.*6\n7.8.9",
+            "******&\n*.....*\n**576.*\n*.....*\n*******",
+            "1*\n\n23456\n..\n.......7",
+            "576",
+            "*",
+            "",
+        ];
+        let neighbourhoods = [
+            Neighbourhood::default(),
+            Neighbourhood::new(NeighbourhoodShape::Chebyshev, 0),
+            Neighbourhood::new(NeighbourhoodShape::Chebyshev, 2),
+            Neighbourhood::new(NeighbourhoodShape::Manhattan, 3),
+            Neighbourhood::new(NeighbourhoodShape::VonNeumann, 2),
+        ];
+        for content in tests {
+            for neighbourhood in neighbourhoods {
+                let config = SchematicConfig {
+                    neighbourhood,
+                    gear_adjacency: GearAdjacency::AtLeast(2),
+                    ..Default::default()
+                };
+                let mut input = MockFile::with_contents(content);
+                let part_sum = run_part_1_with_config::<u64>(input.get_file(), &config).unwrap();
+                let mut input = MockFile::with_contents(content);
+                let gear_sum = run_part_2_with_config::<u64>(input.get_file(), &config).unwrap();
+                let streamed = run_streaming::<u64>(Cursor::new(content), &config).unwrap();
+                assert_eq!(
+                    streamed,
+                    (part_sum, gear_sum),
+                    "{content} {neighbourhood:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_config() {
+        let config = SchematicConfig {
+            gear_adjacency: GearAdjacency::AtLeast(2),
+            ..Default::default()
+        };
+        let events = SchematicStream::with_config(Cursor::new("1.2\n.*.\n3.."), config)
+            .unwrap()
+            .collect::<Result<Vec<SchematicEvent>, _>>()
+            .unwrap();
+        assert_eq!(events[2], SchematicEvent::Gear(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_event_order() {
+        let events = SchematicStream::new(Cursor::new(SAMPLE))
+            .collect::<Result<Vec<SchematicEvent>, _>>()
+            .unwrap();
+        assert_eq!(
+            events[..4],
+            [
+                SchematicEvent::PartNumber(467),
+                SchematicEvent::Gear(vec![467, 35]),
+                SchematicEvent::PartNumber(35),
+                SchematicEvent::PartNumber(633),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let config = SchematicConfig {
+            ragged_rows: RaggedRows::Error,
+            ..Default::default()
+        };
+        let mut stream =
+            SchematicStream::with_config(Cursor::new("1*\n.2\n3"), config.clone()).unwrap();
+        assert_eq!(stream.next(), Some(Ok(SchematicEvent::PartNumber(1))));
+        assert_eq!(stream.next(), Some(Ok(SchematicEvent::Gear(vec![1, 2]))));
+        assert_eq!(
+            stream.next(),
+            Some(Err(SchematicError::Parse(ParseSchematicError {
+                row: 2,
+                col: 1,
+                kind: ParseSchematicErrorKind::RaggedRow {
+                    width: 1,
+                    expected: 2
+                }
+            })))
+        );
+        assert_eq!(stream.next(), None);
+
+        // A blank line is a row of its own, so it is too short rather than skipped.
+        let mut stream = SchematicStream::with_config(Cursor::new("1*\n\n.2"), config).unwrap();
+        assert_eq!(
+            stream.next(),
+            Some(Err(SchematicError::Parse(ParseSchematicError {
+                row: 1,
+                col: 0,
+                kind: ParseSchematicErrorKind::RaggedRow {
+                    width: 0,
+                    expected: 2
+                }
+            })))
+        );
+
+        let result = run_streaming::<u64>(
+            Cursor::new("1.99999999999999999999"),
+            &SchematicConfig::default(),
+        );
+        assert!(matches!(
+            result,
+            Err(SchematicError::Parse(ParseSchematicError {
+                row: 0,
+                col: 2,
+                kind: ParseSchematicErrorKind::NumberTooLarge(_)
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_read_errors() {
+        // The rows before the one that cannot be read are still solved.
+        let mut stream = SchematicStream::new(Cursor::new(b"1*\n.2\n\xff\n".as_slice()));
+        assert_eq!(stream.next(), Some(Ok(SchematicEvent::PartNumber(1))));
+        assert_eq!(stream.next(), Some(Ok(SchematicEvent::Gear(vec![1, 2]))));
+        assert_eq!(
+            stream.next(),
+            Some(Err(SchematicError::Io(ErrorKind::InvalidData)))
+        );
+        assert_eq!(stream.next(), None);
+
+        let config = SchematicConfig {
+            edges: Edges::Torus,
+            ..Default::default()
+        };
+        assert!(matches!(
+            SchematicStream::with_config(Cursor::new("1*"), config.clone()),
+            Err(SchematicError::UnboundedEdges(Edges::Torus))
+        ));
+        assert_eq!(
+            run_streaming::<u64>(Cursor::new("1*"), &config),
+            Err(SchematicError::UnboundedEdges(Edges::Torus))
+        );
+    }
+
+    /// Repeats the same rows forever, so only a streaming solver can make progress on it.
+    struct EndlessSchematic {
+        row: usize,
+    }
+
+    impl Read for EndlessSchematic {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let rows: [&[u8]; 2] = [b"..12..\n", b"...*..\n"];
+            let row = rows[self.row % 2];
+            self.row += 1;
+            buf[..row.len()].copy_from_slice(row);
+            Ok(row.len())
+        }
+    }
+
+    #[test]
+    fn test_unbounded_input() {
+        let stream = SchematicStream::new(BufReader::new(EndlessSchematic { row: 0 }));
+        let events = stream
+            .take(4)
+            .collect::<Result<Vec<SchematicEvent>, _>>()
+            .unwrap();
+        assert_eq!(
+            events,
+            [
+                SchematicEvent::PartNumber(12),
+                SchematicEvent::Gear(vec![12, 12]),
+                SchematicEvent::PartNumber(12),
+                SchematicEvent::Gear(vec![12, 12]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,355 @@
+use std::fmt::Display;
+use std::fs::File;
+
+use crate::common::answer::OverflowError;
+use crate::common::puzzle_input_reader::PuzzleInput;
+
+use super::{NumberValue, ParseSchematicErrorKind, Schematic, SchematicConfig, SchematicError};
+
+const OVERFLOW: OverflowError = OverflowError {
+    type_name: "Decimal",
+};
+
+/// An exact signed decimal, `mantissa / 10^scale`, for the numbers found in tokenized schematics.
+///
+/// Trailing zeros after the point are dropped, so equal values compare equal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+impl Decimal {
+    pub fn new(mut mantissa: i128, mut scale: u32) -> Self {
+        while scale > 0 && mantissa % 10 == 0 {
+            mantissa /= 10;
+            scale -= 1;
+        }
+        Self { mantissa, scale }
+    }
+
+    pub fn zero() -> Self {
+        Self::new(0, 0)
+    }
+
+    /// The mantissa written with `scale` digits after the point, which must be at least the
+    /// decimal's own scale.
+    fn mantissa_at(&self, scale: u32) -> Result<i128, OverflowError> {
+        10i128
+            .checked_pow(scale - self.scale)
+            .and_then(|factor| self.mantissa.checked_mul(factor))
+            .ok_or(OVERFLOW)
+    }
+
+    pub fn try_add(self, other: Self) -> Result<Self, OverflowError> {
+        let scale = self.scale.max(other.scale);
+        let mantissa = self
+            .mantissa_at(scale)?
+            .checked_add(other.mantissa_at(scale)?)
+            .ok_or(OVERFLOW)?;
+        Ok(Self::new(mantissa, scale))
+    }
+
+    pub fn try_mul(self, other: Self) -> Result<Self, OverflowError> {
+        let mantissa = self.mantissa.checked_mul(other.mantissa).ok_or(OVERFLOW)?;
+        Ok(Self::new(mantissa, self.scale + other.scale))
+    }
+
+    /// Parses an optionally signed run of digits with an optional fractional part, as produced by
+    /// the tokenizer, or `None` if it has too many digits.
+    fn parse(text: &str) -> Option<Self> {
+        let (whole, fraction) = text.split_once('.').unwrap_or((text, ""));
+        let mantissa = format!("{whole}{fraction}").parse().ok()?;
+        Some(Self::new(mantissa, fraction.len() as u32))
+    }
+}
+
+impl NumberValue for Decimal {
+    fn parse_number(text: &str) -> Result<Self, ParseSchematicErrorKind> {
+        Self::parse(text).ok_or_else(|| ParseSchematicErrorKind::NumberTooLarge(text.to_string()))
+    }
+}
+
+impl Display for Decimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let scale = self.scale as usize;
+        let digits = format!(
+            "{:0width$}",
+            self.mantissa.unsigned_abs(),
+            width = scale + 1
+        );
+        let (whole, fraction) = digits.split_at(digits.len() - scale);
+        if self.mantissa < 0 {
+            write!(f, "-")?;
+        }
+        write!(f, "{whole}")?;
+        if !fraction.is_empty() {
+            write!(f, ".{fraction}")?;
+        }
+        Ok(())
+    }
+}
+
+/// What the tokenizer recognises on top of the single characters of a [`SchematicConfig`], when
+/// set as its `tokens`.
+///
+/// A `-` or `+` directly before a digit is a sign when `signed` is set, unless it follows a
+/// digit, so `5-3` is still two numbers either side of a symbol. A `.` between two digits is a
+/// decimal point when `decimals` is set. Each of `operators` is one symbol token however many
+/// characters it has; the longest matching operator wins, and numbers are matched before
+/// operators. An operator is only ever a symbol, never a gear, even if it is made of gear
+/// characters.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenConfig {
+    pub signed: bool,
+    pub decimals: bool,
+    pub operators: Vec<String>,
+}
+
+impl Default for TokenConfig {
+    fn default() -> Self {
+        Self {
+            signed: true,
+            decimals: true,
+            operators: vec![],
+        }
+    }
+}
+
+/// A token found by [`TokenConfig::match_at`], with the number of characters it covers.
+pub(super) enum Token {
+    Number(usize),
+    Operator(usize),
+}
+
+impl TokenConfig {
+    /// The number or operator token starting at column `start` of `chars`, if there is one.
+    /// Anything else is left to be classified a character at a time.
+    pub(super) fn match_at(&self, chars: &[char], start: usize) -> Option<Token> {
+        let is_digit = |i: usize| chars.get(i).is_some_and(char::is_ascii_digit);
+        let mut end = start;
+        if self.signed
+            && matches!(chars[end], '-' | '+')
+            && is_digit(end + 1)
+            && !(end > 0 && is_digit(end - 1))
+        {
+            end += 1;
+        }
+        if is_digit(end) {
+            while is_digit(end) {
+                end += 1;
+            }
+            if self.decimals && chars.get(end) == Some(&'.') && is_digit(end + 1) {
+                end += 1;
+                while is_digit(end) {
+                    end += 1;
+                }
+            }
+            return Some(Token::Number(end - start));
+        }
+        self.operators
+            .iter()
+            .map(|operator| operator.chars().collect::<Vec<char>>())
+            .filter(|operator| !operator.is_empty() && chars[start..].starts_with(operator))
+            .map(|operator| operator.len())
+            .max()
+            .map(Token::Operator)
+    }
+}
+
+/// Solves both parts with numbers parsed as [`Decimal`]s, returning the part number sum and the
+/// gear ratio sum.
+///
+/// With [`SchematicConfig::tokens`] set, a signed or decimal number is a single number, so it is
+/// only ever counted once, and an operator is a symbol even when it is made of gear characters.
+/// Without it, the answers are those of the plain solvers.
+pub fn run_tokenized(
+    input: &mut File,
+    config: &SchematicConfig,
+) -> Result<(Decimal, Decimal), SchematicError> {
+    let puzzle_input: PuzzleInput<String> = PuzzleInput::rows_from_file(input)?;
+    let schematic = Schematic::<Decimal>::parse(&puzzle_input, config)?;
+    let part_sum = schematic
+        .numbers
+        .iter()
+        .filter(|number| schematic.is_part_number(number))
+        .try_fold(Decimal::zero(), |total, number| total.try_add(number.value))?;
+    let mut gear_sum = Decimal::zero();
+    for pos in schematic.gear_positions() {
+        let ids = schematic.gear_numbers(pos);
+        if schematic.gear_adjacency.matches(ids.len()) {
+            let ratio = ids.into_iter().try_fold(Decimal::new(1, 0), |ratio, id| {
+                ratio.try_mul(schematic.numbers[id as usize].value)
+            })?;
+            gear_sum = gear_sum.try_add(ratio)?;
+        }
+    }
+    Ok((part_sum, gear_sum))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::grid::Edges;
+    use crate::common::puzzle_input_reader::test::MockFile;
+    use crate::day3::{
+        run_part_1, run_part_1_with_config, run_part_2, run_part_2_with_config,
+        ParseSchematicError, ParseSchematicErrorKind, RaggedRows, SchematicConfig, SchematicError,
+    };
+
+    use super::{run_tokenized, Decimal, TokenConfig};
+
+    fn run(content: &str, token_config: &TokenConfig) -> (String, String) {
+        let config = SchematicConfig {
+            tokens: Some(token_config.clone()),
+            ..SchematicConfig::default()
+        };
+        let mut input = MockFile::with_contents(content);
+        let (part_sum, gear_sum) = run_tokenized(input.get_file(), &config).unwrap();
+        (part_sum.to_string(), gear_sum.to_string())
+    }
+
+    #[test]
+    fn test_decimal() {
+        assert_eq!(Decimal::parse("-12.50"), Some(Decimal::new(-125, 1)));
+        assert_eq!(Decimal::parse(&"9".repeat(40)), None);
+        assert_eq!(Decimal::new(-5, 2).to_string(), "-0.05");
+        assert_eq!(Decimal::new(1200, 0).to_string(), "1200");
+        let sum = Decimal::new(125, 2).try_add(Decimal::new(-3, 0)).unwrap();
+        assert_eq!(sum.to_string(), "-1.75");
+        let product = Decimal::new(-15, 1).try_mul(Decimal::new(-2, 1)).unwrap();
+        assert_eq!(product.to_string(), "0.3");
+        assert!(Decimal::new(i128::MAX, 0)
+            .try_add(Decimal::new(1, 0))
+            .is_err());
+    }
+
+    #[test]
+    fn test_matches_plain_solver() {
+        let content = "467..114..\n...*......\n..35..633.\n......#...\n617*......\n.....+.58.\n..592.....\n......755.\n...$.*....\n.664.598..";
+        let plain = TokenConfig {
+            signed: false,
+            decimals: false,
+            operators: vec![],
+        };
+        let mut input = MockFile::with_contents(content);
+        let part_sum = run_part_1::<u64>(input.get_file()).unwrap();
+        let mut input = MockFile::with_contents(content);
+        let gear_sum = run_part_2::<u64>(input.get_file()).unwrap();
+        assert_eq!(
+            run(content, &plain),
+            (part_sum.to_string(), gear_sum.to_string())
+        );
+    }
+
+    #[test]
+    fn test_signed_numbers() {
+        // The minus sign is part of the number, so it no longer makes -576 a part number.
+        assert_eq!(
+            run("......\n.-576.\n......", &TokenConfig::default()).0,
+            "0"
+        );
+        assert_eq!(
+            run("#.....\n.-576.\n......", &TokenConfig::default()).0,
+            "-576"
+        );
+        // A sign straight after a digit is a symbol.
+        assert_eq!(run("5-3", &TokenConfig::default()).0, "8");
+        assert_eq!(
+            run("-2*+3", &TokenConfig::default()),
+            ("1".into(), "-6".into())
+        );
+    }
+
+    #[test]
+    fn test_decimal_numbers() {
+        assert_eq!(
+            run("1.5*2.25", &TokenConfig::default()),
+            ("3.75".into(), "3.375".into())
+        );
+        // Only a point between digits is a decimal point.
+        assert_eq!(
+            run("1.*5", &TokenConfig::default()),
+            ("5".into(), "0".into())
+        );
+        let integers = TokenConfig {
+            decimals: false,
+            ..TokenConfig::default()
+        };
+        assert_eq!(run("1.5*2", &integers), ("7".into(), "10".into()));
+    }
+
+    #[test]
+    fn test_operators() {
+        let operators = TokenConfig {
+            operators: vec![String::from("->"), String::from("**"), String::from("*")],
+            ..TokenConfig::default()
+        };
+        // `**` is a single symbol, not two gears, and `->` is not a sign.
+        assert_eq!(run("2**3", &operators), ("5".into(), "0".into()));
+        assert_eq!(run("2->3", &operators), ("5".into(), "0".into()));
+        // Without operators, each `*` is a gear between its own neighbours.
+        assert_eq!(
+            run("2**3", &TokenConfig::default()),
+            ("5".into(), "0".into())
+        );
+        assert_eq!(
+            run("2*.\n.*3", &TokenConfig::default()),
+            ("5".into(), "12".into())
+        );
+    }
+
+    #[test]
+    fn test_shares_the_schematic_config() {
+        let tokens = SchematicConfig {
+            tokens: Some(TokenConfig::default()),
+            ..SchematicConfig::default()
+        };
+        // Edges and ragged rows are handled by the same parser as the plain solvers.
+        let config = SchematicConfig {
+            edges: Edges::Torus,
+            ..tokens.clone()
+        };
+        let mut input = MockFile::with_contents("-1.5...#");
+        let (part_sum, _) = run_tokenized(input.get_file(), &config).unwrap();
+        assert_eq!(part_sum.to_string(), "-1.5");
+        let config = SchematicConfig {
+            ragged_rows: RaggedRows::Error,
+            ..tokens.clone()
+        };
+        let mut input = MockFile::with_contents("-1*\n2");
+        assert_eq!(
+            run_tokenized(input.get_file(), &config),
+            Err(SchematicError::Parse(ParseSchematicError {
+                row: 1,
+                col: 1,
+                kind: ParseSchematicErrorKind::RaggedRow {
+                    width: 1,
+                    expected: 3
+                },
+            }))
+        );
+
+        // The plain solvers read operators as symbols, but cannot total signed numbers.
+        let config = SchematicConfig {
+            tokens: Some(TokenConfig {
+                operators: vec![String::from("*")],
+                ..TokenConfig::default()
+            }),
+            ..SchematicConfig::default()
+        };
+        let mut input = MockFile::with_contents("2*3");
+        assert_eq!(
+            run_part_2_with_config::<u64>(input.get_file(), &config),
+            Ok(0)
+        );
+        let mut input = MockFile::with_contents(".-5*");
+        assert_eq!(
+            run_part_1_with_config::<u64>(input.get_file(), &tokens),
+            Err(SchematicError::Parse(ParseSchematicError {
+                row: 0,
+                col: 1,
+                kind: ParseSchematicErrorKind::UnsupportedNumber(String::from("-5")),
+            }))
+        );
+    }
+}
@@ -0,0 +1,338 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::Display;
+use std::fs::File;
+use std::io;
+use std::str::FromStr;
+
+use crate::common::answer::{Answer, OverflowError};
+use crate::common::puzzle_input_reader::PuzzleInput;
+
+/// The ways a scratchcard can fail to parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseCardErrorKind {
+    /// The line does not start with `Card`.
+    MissingHeader,
+    ExpectedNumber,
+    NumberTooLarge,
+    /// A specific separator was expected, such as the `:` after the card id or the `|` between
+    /// the winning numbers and the numbers you have.
+    ExpectedByte(u8),
+}
+
+/// A malformed scratchcard, pointing at the 1-based line and column where parsing stopped. A
+/// card parsed on its own is always on line 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseCardError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseCardErrorKind,
+}
+
+impl Display for ParseCardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match self.kind {
+            ParseCardErrorKind::MissingHeader => write!(f, "expected `Card`"),
+            ParseCardErrorKind::ExpectedNumber => write!(f, "expected a number"),
+            ParseCardErrorKind::NumberTooLarge => write!(f, "number is too large"),
+            ParseCardErrorKind::ExpectedByte(b) => write!(f, "expected `{}`", b as char),
+        }
+    }
+}
+
+impl Error for ParseCardError {}
+
+/// Anything that can stop a day 4 solver from producing an answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScratchcardsError {
+    Parse(ParseCardError),
+    Overflow(OverflowError),
+    Io(io::ErrorKind),
+}
+
+impl Display for ScratchcardsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScratchcardsError::Parse(error) => write!(f, "{error}"),
+            ScratchcardsError::Overflow(error) => write!(f, "{error}"),
+            ScratchcardsError::Io(kind) => write!(f, "could not read the scratchcards: {kind}"),
+        }
+    }
+}
+
+impl Error for ScratchcardsError {}
+
+impl From<ParseCardError> for ScratchcardsError {
+    fn from(error: ParseCardError) -> Self {
+        ScratchcardsError::Parse(error)
+    }
+}
+
+impl From<OverflowError> for ScratchcardsError {
+    fn from(error: OverflowError) -> Self {
+        ScratchcardsError::Overflow(error)
+    }
+}
+
+impl From<io::Error> for ScratchcardsError {
+    fn from(error: io::Error) -> Self {
+        ScratchcardsError::Io(error.kind())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Card {
+    id: usize,
+    winning: Vec<u32>,
+    have: Vec<u32>,
+}
+
+/// Parses the space separated numbers in `value`, which starts at the 0-based column `offset`
+/// of the card.
+fn parse_numbers(value: &str, offset: usize) -> Result<Vec<u32>, ParseCardError> {
+    let mut numbers = vec![];
+    let mut column = offset;
+    for word in value.split(' ') {
+        if !word.is_empty() {
+            if !word.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(ParseCardError {
+                    line: 1,
+                    column: column + 1,
+                    kind: ParseCardErrorKind::ExpectedNumber,
+                });
+            }
+            numbers.push(word.parse().map_err(|_| ParseCardError {
+                line: 1,
+                column: column + 1,
+                kind: ParseCardErrorKind::NumberTooLarge,
+            })?);
+        }
+        column += word.len() + 1;
+    }
+    Ok(numbers)
+}
+
+impl FromStr for Card {
+    type Err = ParseCardError;
+
+    /// Parses `Card <id>: <winning numbers> | <numbers you have>`, with any number of spaces
+    /// between the numbers.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.starts_with("Card") {
+            return Err(ParseCardError {
+                line: 1,
+                column: 1,
+                kind: ParseCardErrorKind::MissingHeader,
+            });
+        }
+        let colon = s.find(':').ok_or(ParseCardError {
+            line: 1,
+            column: s.len() + 1,
+            kind: ParseCardErrorKind::ExpectedByte(b':'),
+        })?;
+        let id = match parse_numbers(&s[4..colon], 4)?.as_slice() {
+            &[id] => id as usize,
+            _ => {
+                return Err(ParseCardError {
+                    line: 1,
+                    column: 5,
+                    kind: ParseCardErrorKind::ExpectedNumber,
+                })
+            }
+        };
+        let bar = s[colon..]
+            .find('|')
+            .map(|bar| colon + bar)
+            .ok_or(ParseCardError {
+                line: 1,
+                column: s.len() + 1,
+                kind: ParseCardErrorKind::ExpectedByte(b'|'),
+            })?;
+        Ok(Card {
+            id,
+            winning: parse_numbers(&s[colon + 1..bar], colon + 1)?,
+            have: parse_numbers(&s[bar + 1..], bar + 1)?,
+        })
+    }
+}
+
+impl Card {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// How many of the numbers you have are winning numbers.
+    pub fn matches(&self) -> usize {
+        let winning = self.winning.iter().collect::<HashSet<&u32>>();
+        self.have
+            .iter()
+            .filter(|number| winning.contains(number))
+            .count()
+    }
+
+    /// One point for the first match, doubled for every match after it.
+    pub fn points<A: Answer>(&self) -> Result<A, OverflowError> {
+        match self.matches() {
+            0 => Ok(A::from_u64(0)),
+            matches => A::try_product((1..matches).map(|_| A::from_u64(2))),
+        }
+    }
+}
+
+/// Parses one card per line, skipping blank lines, and reports errors at their line in `input`.
+fn parse_cards(input: &mut File) -> Result<Vec<Card>, ScratchcardsError> {
+    let puzzle_input: PuzzleInput<String> = PuzzleInput::rows_from_file(input)?;
+    Ok(puzzle_input
+        .into_iter()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(index, line)| {
+            line.parse::<Card>().map_err(|error| ParseCardError {
+                line: index + 1,
+                ..error
+            })
+        })
+        .collect::<Result<_, _>>()?)
+}
+
+pub fn run_part_1<A: Answer>(input: &mut File) -> Result<A, ScratchcardsError> {
+    Ok(parse_cards(input)?
+        .iter()
+        .try_fold(A::from_u64(0), |total, card| total.try_add(card.points()?))?)
+}
+
+/// Counts every card you end up with once each card's matches have won copies of the cards
+/// after it, never past the end of the table.
+///
+/// Each match adds to the count of a single later card, so the work is linear in the size of
+/// the input.
+pub fn run_part_2<A: Answer>(input: &mut File) -> Result<A, ScratchcardsError> {
+    let cards = parse_cards(input)?;
+    let mut copies = vec![A::from_u64(1); cards.len()];
+    for (index, card) in cards.iter().enumerate() {
+        let last = (index + card.matches()).min(cards.len() - 1);
+        for won in index + 1..=last {
+            copies[won] = copies[won].clone().try_add(copies[index].clone())?;
+        }
+    }
+    Ok(A::try_sum(copies)?)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::ErrorKind;
+
+    use crate::common::answer::{BigUint, OverflowError};
+    use crate::common::puzzle_input_reader::test::MockFile;
+
+    use super::{
+        run_part_1, run_part_2, Card, ParseCardError, ParseCardErrorKind, ScratchcardsError,
+    };
+
+    const SAMPLE: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+
+    #[test]
+    fn test_parse() {
+        let card = "Card   12:  1 21 | 21  3".parse::<Card>().unwrap();
+        assert_eq!(card.id(), 12);
+        assert_eq!(card.matches(), 1);
+        let cases = [
+            ("Crd 1: 1 | 2", 1, ParseCardErrorKind::MissingHeader),
+            ("Card 1 1 | 2", 13, ParseCardErrorKind::ExpectedByte(b':')),
+            ("Card x: 1 | 2", 6, ParseCardErrorKind::ExpectedNumber),
+            ("Card 1: 1 2", 12, ParseCardErrorKind::ExpectedByte(b'|')),
+            ("Card 1: 1 a | 2", 11, ParseCardErrorKind::ExpectedNumber),
+            (
+                "Card 1: 1 | 99999999999",
+                13,
+                ParseCardErrorKind::NumberTooLarge,
+            ),
+        ];
+        for (card, column, kind) in cases {
+            assert_eq!(
+                card.parse::<Card>(),
+                Err(ParseCardError {
+                    line: 1,
+                    column,
+                    kind
+                }),
+                "{card:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_errors_point_at_the_line() {
+        let mut input = MockFile::with_contents("Card 1: 1 | 2\n\nCard 3: 1 x | 2\n");
+        let error = run_part_1::<u64>(input.get_file()).unwrap_err();
+        assert_eq!(
+            error,
+            ScratchcardsError::Parse(ParseCardError {
+                line: 3,
+                column: 11,
+                kind: ParseCardErrorKind::ExpectedNumber
+            })
+        );
+        assert_eq!(error.to_string(), "line 3, column 11: expected a number");
+        let mut input = MockFile::with_contents("Card 1: 1 2 | 2\nCard 2 1 | 2");
+        assert_eq!(
+            run_part_2::<u64>(input.get_file()),
+            Err(ScratchcardsError::Parse(ParseCardError {
+                line: 2,
+                column: 13,
+                kind: ParseCardErrorKind::ExpectedByte(b':')
+            }))
+        );
+        let mut input = MockFile::with_bytes(b"Card 1: 1 | \xff");
+        assert_eq!(
+            run_part_1::<u64>(input.get_file()),
+            Err(ScratchcardsError::Io(ErrorKind::InvalidData))
+        );
+    }
+
+    #[test]
+    fn test_overflow() {
+        let winning = (1..=70).map(|n| n.to_string()).collect::<Vec<String>>();
+        let card = format!("Card 1: {} | {}", winning.join(" "), winning.join(" "));
+        let mut input = MockFile::with_contents(&card);
+        assert_eq!(
+            run_part_1::<u64>(input.get_file()),
+            Err(ScratchcardsError::Overflow(OverflowError {
+                type_name: "u64"
+            }))
+        );
+        let mut input = MockFile::with_contents(&card);
+        assert_eq!(
+            run_part_1::<BigUint>(input.get_file()).unwrap().to_string(),
+            "590295810358705651712"
+        );
+    }
+
+    #[test]
+    fn test_copies_stop_at_the_last_card() {
+        let mut input = MockFile::with_contents("Card 1: 1 2 3 | 1 2 3\nCard 2: 4 | 5");
+        assert_eq!(run_part_2::<u64>(input.get_file()), Ok(3));
+    }
+
+    #[test]
+    fn test_sample_input_1() {
+        let mut sample_input = MockFile::with_contents(SAMPLE);
+        let actual_result = run_part_1::<u64>(sample_input.get_file()).unwrap();
+        let expected_result = 13;
+        assert_eq!(actual_result, expected_result);
+    }
+
+    #[test]
+    fn test_sample_input_2() {
+        let mut sample_input = MockFile::with_contents(SAMPLE);
+        let actual_result = run_part_2::<u64>(sample_input.get_file()).unwrap();
+        let expected_result = 30;
+        assert_eq!(actual_result, expected_result);
+    }
+}
@@ -0,0 +1,399 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::fs::File;
+use std::ops::Range;
+
+use crate::common::puzzle_input_reader::PuzzleInput;
+
+/// The ways an almanac can fail to parse. Each carries the offending line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseAlmanacError {
+    /// The first section is not a `seeds: ...` line listing at least one seed.
+    BadSeeds(String),
+    /// Part 2 reads the seeds as start and length pairs, so there must be an even number.
+    OddSeedCount(usize),
+    /// A part 2 seed range, given as its start and length, ends past the largest `u64`.
+    SeedRangeTooLarge(u64, u64),
+    /// A map section does not start with `<source>-to-<destination> map:`.
+    BadMapHeader(String),
+    /// A map line is not three numbers, `destination source length`, that fit in a `u64`.
+    BadRange(String),
+}
+
+impl Display for ParseAlmanacError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseAlmanacError::BadSeeds(line) => write!(f, "expected `seeds: ...`, got {line:?}"),
+            ParseAlmanacError::OddSeedCount(count) => {
+                write!(f, "{count} seeds cannot be read as start and length pairs")
+            }
+            ParseAlmanacError::SeedRangeTooLarge(start, length) => {
+                write!(f, "{length} seeds from {start} do not fit in a u64")
+            }
+            ParseAlmanacError::BadMapHeader(line) => {
+                write!(f, "expected `<source>-to-<destination> map:`, got {line:?}")
+            }
+            ParseAlmanacError::BadRange(line) => {
+                write!(f, "expected `destination source length`, got {line:?}")
+            }
+        }
+    }
+}
+
+impl Error for ParseAlmanacError {}
+
+/// One line of a map: the `length` values from `source` on map to those from `destination` on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct MapRange {
+    destination: u64,
+    source: u64,
+    length: u64,
+}
+
+impl MapRange {
+    fn source_end(&self) -> u64 {
+        self.source + self.length
+    }
+
+    fn map(&self, value: u64) -> u64 {
+        self.destination + (value - self.source)
+    }
+}
+
+/// A map from one category to the next. Values outside every range map to themselves.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AlmanacMap {
+    source: String,
+    destination: String,
+    /// Sorted by source, and assumed not to overlap.
+    ranges: Vec<MapRange>,
+}
+
+impl AlmanacMap {
+    fn parse(section: &PuzzleInput<String>) -> Result<Self, ParseAlmanacError> {
+        let mut lines = section.into_iter();
+        let header = lines.next().map_or("", String::as_str);
+        let (source, destination) = header
+            .strip_suffix(" map:")
+            .and_then(|categories| categories.split_once("-to-"))
+            .ok_or_else(|| ParseAlmanacError::BadMapHeader(header.to_string()))?;
+        let mut ranges = lines
+            .map(|line| {
+                let bad_range = || ParseAlmanacError::BadRange(line.clone());
+                let numbers = line
+                    .split_whitespace()
+                    .map(|number| number.parse::<u64>().map_err(|_| bad_range()))
+                    .collect::<Result<Vec<u64>, ParseAlmanacError>>()?;
+                let &[destination, source, length] = numbers.as_slice() else {
+                    return Err(bad_range());
+                };
+                if destination.checked_add(length).is_none() || source.checked_add(length).is_none()
+                {
+                    return Err(bad_range());
+                }
+                Ok(MapRange {
+                    destination,
+                    source,
+                    length,
+                })
+            })
+            .collect::<Result<Vec<MapRange>, ParseAlmanacError>>()?;
+        ranges.sort_by_key(|range| range.source);
+        Ok(Self {
+            source: source.to_string(),
+            destination: destination.to_string(),
+            ranges,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn destination(&self) -> &str {
+        &self.destination
+    }
+
+    pub fn map(&self, value: u64) -> u64 {
+        self.ranges
+            .iter()
+            .find(|range| (range.source..range.source_end()).contains(&value))
+            .map_or(value, |range| range.map(value))
+    }
+
+    /// Maps every value in `interval`, splitting it wherever it crosses the edge of a range.
+    /// The pieces come out in the order of the values they were mapped from.
+    pub fn map_interval(&self, interval: Range<u64>) -> Vec<Range<u64>> {
+        let mut pieces = vec![];
+        let mut cursor = interval.start;
+        for range in self.ranges.iter() {
+            if cursor >= interval.end {
+                break;
+            }
+            if range.source_end() <= cursor {
+                continue;
+            }
+            if range.source >= interval.end {
+                break;
+            }
+            if cursor < range.source {
+                pieces.push(cursor..range.source);
+                cursor = range.source;
+            }
+            let end = interval.end.min(range.source_end());
+            pieces.push(range.map(cursor)..range.map(cursor) + (end - cursor));
+            cursor = end;
+        }
+        if cursor < interval.end {
+            pieces.push(cursor..interval.end);
+        }
+        pieces
+    }
+}
+
+/// The seeds to plant and the maps that take each seed through to a location.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Almanac {
+    seeds: Vec<u64>,
+    maps: Vec<AlmanacMap>,
+}
+
+impl Almanac {
+    pub fn from_file(input: &mut File) -> Result<Self, ParseAlmanacError> {
+        let sections: Vec<PuzzleInput<String>> = PuzzleInput::sections_from_file(input).unwrap();
+        let (seeds, maps) = match sections.split_first() {
+            Some((seeds, maps)) => (seeds, maps),
+            None => return Err(ParseAlmanacError::BadSeeds(String::new())),
+        };
+        let line = seeds.into_iter().next().unwrap();
+        let bad_seeds = || ParseAlmanacError::BadSeeds(line.clone());
+        if seeds.into_iter().count() > 1 {
+            return Err(bad_seeds());
+        }
+        let seeds = line
+            .strip_prefix("seeds:")
+            .ok_or_else(bad_seeds)?
+            .split_whitespace()
+            .map(|seed| seed.parse().map_err(|_| bad_seeds()))
+            .collect::<Result<Vec<u64>, ParseAlmanacError>>()?;
+        if seeds.is_empty() {
+            return Err(bad_seeds());
+        }
+        Ok(Self {
+            seeds,
+            maps: maps
+                .iter()
+                .map(AlmanacMap::parse)
+                .collect::<Result<Vec<AlmanacMap>, ParseAlmanacError>>()?,
+        })
+    }
+
+    pub fn seeds(&self) -> &[u64] {
+        &self.seeds
+    }
+
+    pub fn maps(&self) -> &[AlmanacMap] {
+        &self.maps
+    }
+
+    /// The seeds read as `start length` pairs, skipping any empty ranges.
+    pub fn seed_ranges(&self) -> Result<Vec<Range<u64>>, ParseAlmanacError> {
+        if !self.seeds.len().is_multiple_of(2) {
+            return Err(ParseAlmanacError::OddSeedCount(self.seeds.len()));
+        }
+        let mut ranges = vec![];
+        for pair in self.seeds.chunks(2) {
+            let (start, length) = (pair[0], pair[1]);
+            let end = start
+                .checked_add(length)
+                .ok_or(ParseAlmanacError::SeedRangeTooLarge(start, length))?;
+            if length > 0 {
+                ranges.push(start..end);
+            }
+        }
+        Ok(ranges)
+    }
+
+    /// Takes `seed` through every map in turn.
+    pub fn location(&self, seed: u64) -> u64 {
+        self.maps.iter().fold(seed, |value, map| map.map(value))
+    }
+
+    /// Takes whole ranges of seeds through every map in turn, so the work depends on how many
+    /// times the ranges get split rather than how many seeds there are.
+    pub fn locations(&self, seeds: Vec<Range<u64>>) -> Vec<Range<u64>> {
+        self.maps.iter().fold(seeds, |intervals, map| {
+            intervals
+                .into_iter()
+                .flat_map(|interval| map.map_interval(interval))
+                .collect()
+        })
+    }
+}
+
+pub fn run_part_1(input: &mut File) -> Result<u64, ParseAlmanacError> {
+    let almanac = Almanac::from_file(input)?;
+    Ok(almanac
+        .seeds()
+        .iter()
+        .map(|&seed| almanac.location(seed))
+        .min()
+        .unwrap())
+}
+
+/// The lowest location of any seed in the seed ranges, or `None` if every range is empty.
+pub fn run_part_2(input: &mut File) -> Result<Option<u64>, ParseAlmanacError> {
+    let almanac = Almanac::from_file(input)?;
+    Ok(almanac
+        .locations(almanac.seed_ranges()?)
+        .into_iter()
+        .map(|interval| interval.start)
+        .min())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::puzzle_input_reader::test::MockFile;
+
+    use super::{run_part_1, run_part_2, Almanac, ParseAlmanacError};
+
+    const SAMPLE: &str = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4
+";
+
+    fn almanac(contents: &str) -> Almanac {
+        let mut input = MockFile::with_contents(contents);
+        Almanac::from_file(input.get_file()).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let almanac = almanac(SAMPLE);
+        assert_eq!(almanac.seeds(), &[79, 14, 55, 13]);
+        assert_eq!(almanac.maps().len(), 7);
+        assert_eq!(almanac.maps()[0].source(), "seed");
+        assert_eq!(almanac.maps()[6].destination(), "location");
+        assert_eq!(
+            almanac
+                .seeds()
+                .iter()
+                .map(|&seed| almanac.location(seed))
+                .collect::<Vec<u64>>(),
+            vec![82, 43, 86, 35]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let cases = [
+            ("", ParseAlmanacError::BadSeeds(String::new())),
+            (
+                "seed: 1",
+                ParseAlmanacError::BadSeeds("seed: 1".to_string()),
+            ),
+            ("seeds:", ParseAlmanacError::BadSeeds("seeds:".to_string())),
+            (
+                "seeds: 1\n\nseed to soil:\n1 2 3",
+                ParseAlmanacError::BadMapHeader("seed to soil:".to_string()),
+            ),
+            (
+                "seeds: 1\n\na-to-b map:\n1 2",
+                ParseAlmanacError::BadRange("1 2".to_string()),
+            ),
+            (
+                "seeds: 1\n\na-to-b map:\n18446744073709551615 0 2",
+                ParseAlmanacError::BadRange("18446744073709551615 0 2".to_string()),
+            ),
+        ];
+        for (contents, error) in cases {
+            let mut input = MockFile::with_contents(contents);
+            assert_eq!(
+                Almanac::from_file(input.get_file()),
+                Err(error),
+                "{contents:?}"
+            );
+        }
+        assert_eq!(
+            almanac("seeds: 1 2 3").seed_ranges(),
+            Err(ParseAlmanacError::OddSeedCount(3))
+        );
+    }
+
+    #[test]
+    fn test_map_interval() {
+        let almanac = almanac("seeds: 1\n\na-to-b map:\n100 10 5\n0 20 5");
+        let map = &almanac.maps()[0];
+        assert_eq!(
+            map.map_interval(0..30),
+            vec![0..10, 100..105, 15..20, 0..5, 25..30]
+        );
+        assert_eq!(map.map_interval(12..14), vec![102..104]);
+        assert_eq!(map.map_interval(30..40), vec![30..40]);
+        assert_eq!(map.map_interval(5..5), vec![]);
+    }
+
+    #[test]
+    fn test_huge_seed_ranges() {
+        // Far too many seeds to map one at a time.
+        let contents = "seeds: 0 18446744073709551615\n\na-to-b map:\n0 1000 5\n";
+        let mut input = MockFile::with_contents(contents);
+        assert_eq!(run_part_2(input.get_file()), Ok(Some(0)));
+        let mut input =
+            MockFile::with_contents("seeds: 1000 5000000000\n\na-to-b map:\n7 1000 5\n");
+        assert_eq!(run_part_2(input.get_file()), Ok(Some(7)));
+        let mut input = MockFile::with_contents("seeds: 1 0\n");
+        assert_eq!(run_part_2(input.get_file()), Ok(None));
+        let mut input = MockFile::with_contents("seeds: 1 18446744073709551615\n");
+        assert_eq!(
+            run_part_2(input.get_file()),
+            Err(ParseAlmanacError::SeedRangeTooLarge(1, u64::MAX))
+        );
+    }
+
+    #[test]
+    fn test_sample_input_1() {
+        let mut sample_input = MockFile::with_contents(SAMPLE);
+        let actual_result = run_part_1(sample_input.get_file()).unwrap();
+        let expected_result = 35;
+        assert_eq!(actual_result, expected_result);
+    }
+
+    #[test]
+    fn test_sample_input_2() {
+        let mut sample_input = MockFile::with_contents(SAMPLE);
+        let actual_result = run_part_2(sample_input.get_file()).unwrap();
+        let expected_result = Some(46);
+        assert_eq!(actual_result, expected_result);
+    }
+}
@@ -0,0 +1,289 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::fs::File;
+
+use crate::common::answer::{Answer, OverflowError};
+use crate::common::puzzle_input_reader::PuzzleInput;
+
+/// The ways the race sheet can fail to parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseRacesError {
+    /// The sheet needs a `Time:` line followed by a `Distance:` line.
+    MissingLine(&'static str),
+    /// A value that is not a number, or is too large to be one.
+    BadNumber(String),
+    /// The two lines list different numbers of races.
+    MismatchedRaces { times: usize, distances: usize },
+}
+
+impl Display for ParseRacesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseRacesError::MissingLine(label) => write!(f, "expected a `{label}` line"),
+            ParseRacesError::BadNumber(value) => write!(f, "{value:?} is not a valid number"),
+            ParseRacesError::MismatchedRaces { times, distances } => {
+                write!(f, "{times} race times but {distances} record distances")
+            }
+        }
+    }
+}
+
+impl Error for ParseRacesError {}
+
+/// Anything that can stop a day 6 solver from producing an answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RaceError {
+    Parse(ParseRacesError),
+    Overflow(OverflowError),
+}
+
+impl Display for RaceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RaceError::Parse(error) => write!(f, "{error}"),
+            RaceError::Overflow(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for RaceError {}
+
+impl From<ParseRacesError> for RaceError {
+    fn from(error: ParseRacesError) -> Self {
+        RaceError::Parse(error)
+    }
+}
+
+impl From<OverflowError> for RaceError {
+    fn from(error: OverflowError) -> Self {
+        RaceError::Overflow(error)
+    }
+}
+
+/// A race lasting `time` milliseconds, and the furthest any boat has gone in it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Race {
+    pub time: u64,
+    pub record: u64,
+}
+
+impl Race {
+    /// How many whole milliseconds of holding the button beat the record.
+    ///
+    /// Holding for `h` goes `h * (time - h)`, so the winning holds are those strictly between the
+    /// roots of `h² - time·h + record = 0`. An integer square root lands within one of the lower
+    /// root, and the exact bound is found by checking the distances either side of it. The
+    /// distances are symmetric about `time / 2`, so the upper bound mirrors the lower one.
+    pub fn ways_to_win(&self) -> u64 {
+        let (time, record) = (self.time as u128, self.record as u128);
+        let beats = |hold: u128| hold * (time - hold) > record;
+        if !beats(time / 2) {
+            return 0;
+        }
+        // The best hold beats the record, so the discriminant is positive.
+        let root = (time * time - 4 * record).isqrt();
+        let mut lowest = (time - root) / 2;
+        while lowest > 0 && beats(lowest - 1) {
+            lowest -= 1;
+        }
+        while !beats(lowest) {
+            lowest += 1;
+        }
+        (time - 2 * lowest + 1) as u64
+    }
+}
+
+/// Parses the numbers after `label` on `line`.
+fn parse_line(line: Option<&String>, label: &'static str) -> Result<Vec<String>, ParseRacesError> {
+    Ok(line
+        .and_then(|line| line.strip_prefix(label))
+        .ok_or(ParseRacesError::MissingLine(label))?
+        .split_whitespace()
+        .map(String::from)
+        .collect())
+}
+
+fn parse_number(value: &str) -> Result<u64, ParseRacesError> {
+    value
+        .parse()
+        .map_err(|_| ParseRacesError::BadNumber(value.to_string()))
+}
+
+/// The two lines of the race sheet, as written.
+struct RaceSheet {
+    times: Vec<String>,
+    records: Vec<String>,
+}
+
+impl RaceSheet {
+    fn from_file(input: &mut File) -> Result<Self, ParseRacesError> {
+        let puzzle_input: PuzzleInput<String> = PuzzleInput::from_file(input).unwrap();
+        let mut lines = puzzle_input.into_iter();
+        let times = parse_line(lines.next(), "Time:")?;
+        let records = parse_line(lines.next(), "Distance:")?;
+        if times.len() != records.len() {
+            return Err(ParseRacesError::MismatchedRaces {
+                times: times.len(),
+                distances: records.len(),
+            });
+        }
+        Ok(Self { times, records })
+    }
+
+    /// Each column of the sheet as a race of its own.
+    fn races(&self) -> Result<Vec<Race>, ParseRacesError> {
+        self.times
+            .iter()
+            .zip(self.records.iter())
+            .map(|(time, record)| {
+                Ok(Race {
+                    time: parse_number(time)?,
+                    record: parse_number(record)?,
+                })
+            })
+            .collect()
+    }
+
+    /// The whole sheet as one race, ignoring the spaces between the numbers on each line.
+    fn kerned_race(&self) -> Result<Race, ParseRacesError> {
+        Ok(Race {
+            time: parse_number(&self.times.concat())?,
+            record: parse_number(&self.records.concat())?,
+        })
+    }
+}
+
+pub fn run_part_1<A: Answer>(input: &mut File) -> Result<A, RaceError> {
+    let races = RaceSheet::from_file(input)?.races()?;
+    Ok(A::try_product(
+        races.iter().map(|race| A::from_u64(race.ways_to_win())),
+    )?)
+}
+
+pub fn run_part_2<A: Answer>(input: &mut File) -> Result<A, RaceError> {
+    let race = RaceSheet::from_file(input)?.kerned_race()?;
+    Ok(A::from_u64(race.ways_to_win()))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::puzzle_input_reader::test::MockFile;
+
+    use super::{run_part_1, run_part_2, ParseRacesError, Race, RaceError};
+
+    const SAMPLE: &str = "Time:      7  15   30\nDistance:  9  40  200";
+
+    #[test]
+    fn test_tied_records() {
+        // Holding for 10 or 20 exactly ties the record of 200, which does not win.
+        assert_eq!(
+            Race {
+                time: 30,
+                record: 200
+            }
+            .ways_to_win(),
+            9
+        );
+        assert_eq!(
+            Race {
+                time: 30,
+                record: 199
+            }
+            .ways_to_win(),
+            11
+        );
+        // The best possible hold only ties.
+        assert_eq!(Race { time: 4, record: 4 }.ways_to_win(), 0);
+        assert_eq!(Race { time: 4, record: 3 }.ways_to_win(), 1);
+        assert_eq!(Race { time: 5, record: 6 }.ways_to_win(), 0);
+        assert_eq!(Race { time: 5, record: 5 }.ways_to_win(), 2);
+        assert_eq!(Race { time: 0, record: 0 }.ways_to_win(), 0);
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        for time in 0..60u64 {
+            for record in 0..=time * time / 4 + 1 {
+                let expected = (0..=time)
+                    .filter(|hold| hold * (time - hold) > record)
+                    .count() as u64;
+                assert_eq!(
+                    Race { time, record }.ways_to_win(),
+                    expected,
+                    "{time} {record}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_large_races() {
+        // Squaring the time needs more than 64 bits.
+        let race = Race {
+            time: u64::MAX,
+            record: 0,
+        };
+        assert_eq!(race.ways_to_win(), u64::MAX - 1);
+        let time = 4_000_000_000u64;
+        let tied = (time / 2) * (time / 2);
+        assert_eq!(Race { time, record: tied }.ways_to_win(), 0);
+        assert_eq!(
+            Race {
+                time,
+                record: tied - 1
+            }
+            .ways_to_win(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let cases = [
+            ("Distance: 9", ParseRacesError::MissingLine("Time:")),
+            ("Time: 7", ParseRacesError::MissingLine("Distance:")),
+            (
+                "Time: 7 x\nDistance: 9 40",
+                ParseRacesError::BadNumber("x".to_string()),
+            ),
+            (
+                "Time: 7 15\nDistance: 9",
+                ParseRacesError::MismatchedRaces {
+                    times: 2,
+                    distances: 1,
+                },
+            ),
+        ];
+        for (contents, error) in cases {
+            let mut input = MockFile::with_contents(contents);
+            assert_eq!(
+                run_part_1::<u64>(input.get_file()),
+                Err(RaceError::Parse(error)),
+                "{contents:?}"
+            );
+        }
+        let mut input = MockFile::with_contents("Time: 99999999999 99999999999\nDistance: 1 1");
+        assert_eq!(
+            run_part_2::<u64>(input.get_file()),
+            Err(RaceError::Parse(ParseRacesError::BadNumber(
+                "9999999999999999999999".to_string()
+            )))
+        );
+    }
+
+    #[test]
+    fn test_sample_input_1() {
+        let mut sample_input = MockFile::with_contents(SAMPLE);
+        let actual_result = run_part_1::<u64>(sample_input.get_file()).unwrap();
+        let expected_result = 288;
+        assert_eq!(actual_result, expected_result);
+    }
+
+    #[test]
+    fn test_sample_input_2() {
+        let mut sample_input = MockFile::with_contents(SAMPLE);
+        let actual_result = run_part_2::<u64>(sample_input.get_file()).unwrap();
+        let expected_result = 71503;
+        assert_eq!(actual_result, expected_result);
+    }
+}
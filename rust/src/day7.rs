@@ -0,0 +1,321 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::fs::File;
+
+use crate::common::answer::{Answer, OverflowError};
+use crate::common::puzzle_input_reader::PuzzleInput;
+
+const CARDS: &str = "23456789TJQKA";
+const HAND_SIZE: usize = 5;
+
+/// Whether `J` is a jack or a joker.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rules {
+    /// `J` is a jack, ranked between `T` and `Q`.
+    #[default]
+    Standard,
+    /// `J` is a joker: it stands in for whichever card makes the strongest category, but is the
+    /// weakest card when breaking ties.
+    Jokers,
+}
+
+impl Rules {
+    fn strength(&self, card: char) -> u8 {
+        match (self, card) {
+            (Rules::Jokers, 'J') => 0,
+            _ => CARDS.find(card).unwrap() as u8 + 1,
+        }
+    }
+}
+
+/// The kinds of hand, weakest first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HandCategory {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    FullHouse,
+    FourOfAKind,
+    FiveOfAKind,
+}
+
+/// The ways a hand or a line of hands and bids can fail to parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseHandError {
+    /// A hand is always five cards.
+    WrongSize(usize),
+    BadCard(char),
+    /// A line is a hand, a space, and a bid.
+    MissingBid,
+    BadBid(String),
+}
+
+impl Display for ParseHandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseHandError::WrongSize(size) => {
+                write!(f, "a hand has {HAND_SIZE} cards, not {size}")
+            }
+            ParseHandError::BadCard(card) => write!(f, "{card:?} is not a card"),
+            ParseHandError::MissingBid => write!(f, "expected a hand followed by a bid"),
+            ParseHandError::BadBid(bid) => write!(f, "{bid:?} is not a valid bid"),
+        }
+    }
+}
+
+impl Error for ParseHandError {}
+
+/// Anything that can stop a day 7 solver from producing an answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CamelCardsError {
+    Parse(ParseHandError),
+    Overflow(OverflowError),
+}
+
+impl Display for CamelCardsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CamelCardsError::Parse(error) => write!(f, "{error}"),
+            CamelCardsError::Overflow(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for CamelCardsError {}
+
+impl From<ParseHandError> for CamelCardsError {
+    fn from(error: ParseHandError) -> Self {
+        CamelCardsError::Parse(error)
+    }
+}
+
+impl From<OverflowError> for CamelCardsError {
+    fn from(error: OverflowError) -> Self {
+        CamelCardsError::Overflow(error)
+    }
+}
+
+/// A hand of Camel Cards, ordered by category and then card by card from the first.
+///
+/// Both the category and the card strengths depend on the [`Rules`] the hand was parsed with, so
+/// only hands parsed with the same rules are meaningfully compared.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CamelHand {
+    // The field order is the comparison order.
+    category: HandCategory,
+    strengths: [u8; HAND_SIZE],
+    rules: Rules,
+    cards: [char; HAND_SIZE],
+}
+
+impl CamelHand {
+    pub fn parse(value: &str, rules: Rules) -> Result<Self, ParseHandError> {
+        let cards = value.chars().collect::<Vec<char>>();
+        let cards: [char; HAND_SIZE] = cards
+            .as_slice()
+            .try_into()
+            .map_err(|_| ParseHandError::WrongSize(cards.len()))?;
+        if let Some(&card) = cards.iter().find(|&&card| !CARDS.contains(card)) {
+            return Err(ParseHandError::BadCard(card));
+        }
+        Ok(Self {
+            category: Self::categorise(&cards, rules),
+            strengths: cards.map(|card| rules.strength(card)),
+            rules,
+            cards,
+        })
+    }
+
+    fn categorise(cards: &[char; HAND_SIZE], rules: Rules) -> HandCategory {
+        let mut counts = [0; CARDS.len()];
+        let mut jokers = 0;
+        for &card in cards {
+            if rules == Rules::Jokers && card == 'J' {
+                jokers += 1;
+            } else {
+                counts[CARDS.find(card).unwrap()] += 1;
+            }
+        }
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+        // Jokers always do best joining the largest group.
+        match (counts[0] + jokers, counts[1]) {
+            (5, _) => HandCategory::FiveOfAKind,
+            (4, _) => HandCategory::FourOfAKind,
+            (3, 2) => HandCategory::FullHouse,
+            (3, _) => HandCategory::ThreeOfAKind,
+            (2, 2) => HandCategory::TwoPair,
+            (2, _) => HandCategory::OnePair,
+            _ => HandCategory::HighCard,
+        }
+    }
+
+    pub fn category(&self) -> HandCategory {
+        self.category
+    }
+
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+}
+
+impl Display for CamelHand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.cards.iter().collect::<String>())
+    }
+}
+
+/// Parses `<hand> <bid>` lines.
+fn parse_plays(input: &mut File, rules: Rules) -> Result<Vec<(CamelHand, u64)>, ParseHandError> {
+    let puzzle_input: PuzzleInput<String> = PuzzleInput::from_file(input).unwrap();
+    puzzle_input
+        .into_iter()
+        .map(|line| {
+            let (hand, bid) = line.split_once(' ').ok_or(ParseHandError::MissingBid)?;
+            let bid = bid
+                .trim()
+                .parse()
+                .map_err(|_| ParseHandError::BadBid(bid.to_string()))?;
+            Ok((CamelHand::parse(hand, rules)?, bid))
+        })
+        .collect()
+}
+
+/// Ranks every hand from the weakest, at rank 1, and totals each bid times its hand's rank.
+pub fn total_winnings<A: Answer>(mut plays: Vec<(CamelHand, u64)>) -> Result<A, OverflowError> {
+    plays.sort();
+    plays
+        .into_iter()
+        .zip(1..)
+        .try_fold(A::from_u64(0), |total, ((_, bid), rank)| {
+            total.try_add(A::from_u64(bid).try_mul(A::from_u64(rank))?)
+        })
+}
+
+pub fn run_part_1<A: Answer>(input: &mut File) -> Result<A, CamelCardsError> {
+    Ok(total_winnings(parse_plays(input, Rules::Standard)?)?)
+}
+
+pub fn run_part_2<A: Answer>(input: &mut File) -> Result<A, CamelCardsError> {
+    Ok(total_winnings(parse_plays(input, Rules::Jokers)?)?)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::puzzle_input_reader::test::MockFile;
+
+    use super::{
+        run_part_1, run_part_2, CamelCardsError, CamelHand, HandCategory, ParseHandError, Rules,
+    };
+
+    const SAMPLE: &str = "32T3K 765\nT55J5 684\nKK677 28\nKTJJT 220\nQQQJA 483";
+
+    fn hand(cards: &str, rules: Rules) -> CamelHand {
+        CamelHand::parse(cards, rules).unwrap()
+    }
+
+    #[test]
+    fn test_categories() {
+        let cases = [
+            (
+                "AAAAA",
+                HandCategory::FiveOfAKind,
+                HandCategory::FiveOfAKind,
+            ),
+            (
+                "AA8AA",
+                HandCategory::FourOfAKind,
+                HandCategory::FourOfAKind,
+            ),
+            ("23332", HandCategory::FullHouse, HandCategory::FullHouse),
+            (
+                "TTT98",
+                HandCategory::ThreeOfAKind,
+                HandCategory::ThreeOfAKind,
+            ),
+            ("23432", HandCategory::TwoPair, HandCategory::TwoPair),
+            ("A23A4", HandCategory::OnePair, HandCategory::OnePair),
+            ("23456", HandCategory::HighCard, HandCategory::HighCard),
+            (
+                "JJJJJ",
+                HandCategory::FiveOfAKind,
+                HandCategory::FiveOfAKind,
+            ),
+            ("KTJJT", HandCategory::TwoPair, HandCategory::FourOfAKind),
+            ("2233J", HandCategory::TwoPair, HandCategory::FullHouse),
+            ("2345J", HandCategory::HighCard, HandCategory::OnePair),
+            ("22JJ3", HandCategory::TwoPair, HandCategory::FourOfAKind),
+        ];
+        for (cards, standard, jokers) in cases {
+            assert_eq!(hand(cards, Rules::Standard).category(), standard, "{cards}");
+            assert_eq!(hand(cards, Rules::Jokers).category(), jokers, "{cards}");
+        }
+    }
+
+    #[test]
+    fn test_ordering() {
+        // Category first, then the first card that differs.
+        assert!(hand("33332", Rules::Standard) > hand("2AAAA", Rules::Standard));
+        assert!(hand("77888", Rules::Standard) > hand("77788", Rules::Standard));
+        // A joker is the weakest card when breaking ties, even below a 2.
+        assert!(hand("JKKK2", Rules::Jokers) < hand("QQQQ2", Rules::Jokers));
+        assert!(hand("J2345", Rules::Jokers) < hand("22345", Rules::Jokers));
+        // As a jack it ranks above a ten instead.
+        assert!(hand("JTTT2", Rules::Standard) > hand("TJJJ2", Rules::Standard));
+        assert!(hand("JTTT2", Rules::Jokers) < hand("TJJJ2", Rules::Jokers));
+        assert!(hand("JJJJJ", Rules::Jokers) < hand("22222", Rules::Jokers));
+
+        let mut hands = ["QQQJA", "T55J5", "KK677", "KTJJT", "32T3K"]
+            .map(|cards| hand(cards, Rules::Jokers))
+            .to_vec();
+        hands.sort();
+        assert_eq!(
+            hands
+                .iter()
+                .map(CamelHand::to_string)
+                .collect::<Vec<String>>(),
+            vec!["32T3K", "KK677", "T55J5", "QQQJA", "KTJJT"]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            CamelHand::parse("AAAA", Rules::Standard),
+            Err(ParseHandError::WrongSize(4))
+        );
+        assert_eq!(
+            CamelHand::parse("AAAA1", Rules::Standard),
+            Err(ParseHandError::BadCard('1'))
+        );
+        let cases = [
+            ("32T3K", ParseHandError::MissingBid),
+            ("32T3K x", ParseHandError::BadBid("x".to_string())),
+            ("32T3 1", ParseHandError::WrongSize(4)),
+        ];
+        for (contents, error) in cases {
+            let mut input = MockFile::with_contents(contents);
+            assert_eq!(
+                run_part_1::<u64>(input.get_file()),
+                Err(CamelCardsError::Parse(error)),
+                "{contents:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sample_input_1() {
+        let mut sample_input = MockFile::with_contents(SAMPLE);
+        let actual_result = run_part_1::<u64>(sample_input.get_file()).unwrap();
+        let expected_result = 6440;
+        assert_eq!(actual_result, expected_result);
+    }
+
+    #[test]
+    fn test_sample_input_2() {
+        let mut sample_input = MockFile::with_contents(SAMPLE);
+        let actual_result = run_part_2::<u64>(sample_input.get_file()).unwrap();
+        let expected_result = 5905;
+        assert_eq!(actual_result, expected_result);
+    }
+}
@@ -0,0 +1,481 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+use std::fs::File;
+
+use crate::common::answer::{Answer, OverflowError};
+use crate::common::puzzle_input_reader::PuzzleInput;
+
+/// The ways the map of the network can fail to parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseNetworkError {
+    /// The first line is not a non-empty run of `L` and `R`.
+    BadInstructions(String),
+    /// A line is not `<node> = (<left>, <right>)`.
+    BadNode(String),
+    DuplicateNode(String),
+    /// A node leads to a node that is never defined.
+    UnknownNode(String),
+}
+
+impl Display for ParseNetworkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseNetworkError::BadInstructions(line) => {
+                write!(f, "expected a line of `L` and `R`, got {line:?}")
+            }
+            ParseNetworkError::BadNode(line) => {
+                write!(f, "expected `<node> = (<left>, <right>)`, got {line:?}")
+            }
+            ParseNetworkError::DuplicateNode(name) => write!(f, "node {name} is defined twice"),
+            ParseNetworkError::UnknownNode(name) => write!(f, "node {name} is never defined"),
+        }
+    }
+}
+
+impl Error for ParseNetworkError {}
+
+/// Where a ghost starting at one node ends up, step by step, once its path starts repeating.
+///
+/// A ghost's position and how far through the instructions it is together decide everything
+/// after, so its path is a `tail` of steps followed by a loop of `length` steps repeated forever.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GhostCycle {
+    pub start: String,
+    pub tail: u64,
+    pub length: u64,
+    /// The steps, before the loop first repeats, at which the ghost is on a node ending in `Z`.
+    pub goals: Vec<u64>,
+}
+
+impl GhostCycle {
+    /// The number of steps `p` such that the ghost is on a goal at every multiple of `p` and at
+    /// no other step, if there is one. This is what lets the least common multiple of every
+    /// ghost's `p` be the first step at which they are all on a goal.
+    ///
+    /// It holds when the first goal is already inside the loop, and the goals are evenly spaced
+    /// by that many steps up to the end of the loop.
+    pub fn regular_period(&self) -> Option<u64> {
+        let period = *self.goals.first()?;
+        let evenly_spaced = period >= self.tail
+            && self.length.is_multiple_of(period)
+            && self
+                .goals
+                .iter()
+                .copied()
+                .eq((1..=self.length / period).map(|k| k * period));
+        evenly_spaced.then_some(period)
+    }
+}
+
+/// Anything that can stop a day 8 solver from producing an answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkError {
+    Parse(ParseNetworkError),
+    /// There is no node with this name to start from or finish at.
+    MissingNode(String),
+    /// No node ends in `A`, so there are no ghosts.
+    NoGhosts,
+    /// Following the instructions from this node loops forever without reaching the goal.
+    Unreachable(String),
+    /// A ghost's goals are not evenly spaced, so the ghosts' periods cannot be combined.
+    IrregularCycle(GhostCycle),
+    Overflow(OverflowError),
+}
+
+impl Display for NetworkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetworkError::Parse(error) => write!(f, "{error}"),
+            NetworkError::MissingNode(name) => write!(f, "there is no node {name}"),
+            NetworkError::NoGhosts => write!(f, "no node ends in A"),
+            NetworkError::Unreachable(start) => write!(f, "{start} never reaches the goal"),
+            NetworkError::IrregularCycle(cycle) => write!(
+                f,
+                "the ghost from {} reaches a goal at steps {:?} in a loop of {} after {} steps, \
+                 not at every multiple of one period",
+                cycle.start, cycle.goals, cycle.length, cycle.tail
+            ),
+            NetworkError::Overflow(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for NetworkError {}
+
+impl From<ParseNetworkError> for NetworkError {
+    fn from(error: ParseNetworkError) -> Self {
+        NetworkError::Parse(error)
+    }
+}
+
+impl From<OverflowError> for NetworkError {
+    fn from(error: OverflowError) -> Self {
+        NetworkError::Overflow(error)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Direction {
+    Left,
+    Right,
+}
+
+/// A node, with the indices of the nodes to its left and right.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Node {
+    name: String,
+    left: usize,
+    right: usize,
+}
+
+/// The left/right instructions and the nodes they are followed through.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Network {
+    instructions: Vec<Direction>,
+    nodes: Vec<Node>,
+    ids: HashMap<String, usize>,
+}
+
+/// Splits `<node> = (<left>, <right>)` into its three names.
+fn parse_node(line: &str) -> Option<(&str, &str, &str)> {
+    let (name, targets) = line.split_once(" = ")?;
+    let (left, right) = targets
+        .strip_prefix('(')?
+        .strip_suffix(')')?
+        .split_once(", ")?;
+    let is_name = |name: &str| !name.is_empty() && name.chars().all(char::is_alphanumeric);
+    (is_name(name) && is_name(left) && is_name(right)).then_some((name, left, right))
+}
+
+impl Network {
+    pub fn from_file(input: &mut File) -> Result<Self, ParseNetworkError> {
+        let puzzle_input: PuzzleInput<String> = PuzzleInput::from_file(input).unwrap();
+        let mut lines = puzzle_input.into_iter();
+        let first = lines.next().map_or("", String::as_str);
+        let instructions = first
+            .chars()
+            .map(|direction| match direction {
+                'L' => Some(Direction::Left),
+                'R' => Some(Direction::Right),
+                _ => None,
+            })
+            .collect::<Option<Vec<Direction>>>()
+            .filter(|instructions| !instructions.is_empty())
+            .ok_or_else(|| ParseNetworkError::BadInstructions(first.to_string()))?;
+
+        let mut names = vec![];
+        let mut ids = HashMap::new();
+        for line in lines {
+            let (name, left, right) =
+                parse_node(line).ok_or_else(|| ParseNetworkError::BadNode(line.clone()))?;
+            if ids.insert(name.to_string(), names.len()).is_some() {
+                return Err(ParseNetworkError::DuplicateNode(name.to_string()));
+            }
+            names.push((name, left, right));
+        }
+        let id = |name: &str| {
+            ids.get(name)
+                .copied()
+                .ok_or_else(|| ParseNetworkError::UnknownNode(name.to_string()))
+        };
+        let nodes = names
+            .iter()
+            .map(|&(name, left, right)| {
+                Ok(Node {
+                    name: name.to_string(),
+                    left: id(left)?,
+                    right: id(right)?,
+                })
+            })
+            .collect::<Result<Vec<Node>, ParseNetworkError>>()?;
+        Ok(Self {
+            instructions,
+            nodes,
+            ids,
+        })
+    }
+
+    fn id(&self, name: &str) -> Result<usize, NetworkError> {
+        self.ids
+            .get(name)
+            .copied()
+            .ok_or_else(|| NetworkError::MissingNode(name.to_string()))
+    }
+
+    /// The node after `node` on step `step`.
+    fn step(&self, node: usize, step: u64) -> usize {
+        match self.instructions[(step % self.instructions.len() as u64) as usize] {
+            Direction::Left => self.nodes[node].left,
+            Direction::Right => self.nodes[node].right,
+        }
+    }
+
+    /// How many steps it takes from `from` to `to`.
+    pub fn steps(&self, from: &str, to: &str) -> Result<u64, NetworkError> {
+        let (from_id, to_id) = (self.id(from)?, self.id(to)?);
+        let cycle = self.ghost_cycle(from_id);
+        let mut node = from_id;
+        // Every reachable state has been seen by the end of the tail and the first loop.
+        for step in 0..cycle.tail + cycle.length {
+            if node == to_id {
+                return Ok(step);
+            }
+            node = self.step(node, step);
+        }
+        Err(NetworkError::Unreachable(from.to_string()))
+    }
+
+    /// Follows the instructions from `start` until the ghost is on a node it has been on before,
+    /// at the same point in the instructions.
+    fn ghost_cycle(&self, start: usize) -> GhostCycle {
+        let period = self.instructions.len() as u64;
+        let mut seen = HashMap::new();
+        let mut goals = vec![];
+        let mut node = start;
+        let mut step = 0;
+        let tail = loop {
+            if let Some(&first) = seen.get(&(node, step % period)) {
+                break first;
+            }
+            seen.insert((node, step % period), step);
+            if self.nodes[node].name.ends_with('Z') {
+                goals.push(step);
+            }
+            node = self.step(node, step);
+            step += 1;
+        };
+        GhostCycle {
+            start: self.nodes[start].name.clone(),
+            tail,
+            length: step - tail,
+            goals,
+        }
+    }
+
+    /// The paths of every ghost, one for each node ending in `A`, in the order the nodes are
+    /// listed.
+    pub fn ghost_cycles(&self) -> Vec<GhostCycle> {
+        (0..self.nodes.len())
+            .filter(|&node| self.nodes[node].name.ends_with('A'))
+            .map(|node| self.ghost_cycle(node))
+            .collect()
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// The least common multiple of `values`, computed without overflowing along the way by keeping
+/// it as a product of factors that each fit in a `u64`.
+fn lcm<A: Answer>(values: impl IntoIterator<Item = u64>) -> Result<A, OverflowError> {
+    let mut factors: Vec<u64> = vec![];
+    for value in values {
+        let new_factor = factors.iter().fold(value, |remaining, &factor| {
+            remaining / gcd(remaining, factor)
+        });
+        factors.push(new_factor);
+    }
+    A::try_product(factors.into_iter().map(A::from_u64))
+}
+
+pub fn run_part_1<A: Answer>(input: &mut File) -> Result<A, NetworkError> {
+    let network = Network::from_file(input)?;
+    Ok(A::from_u64(network.steps("AAA", "ZZZ")?))
+}
+
+/// Counts the steps until every ghost is on a node ending in `Z` at once.
+///
+/// That is the least common multiple of how often each ghost reaches a goal only if every
+/// ghost's goals are evenly spaced from the start, as [`GhostCycle::regular_period`] checks. The
+/// first ghost that never reaches a goal, or whose path does not fit, is returned as an error
+/// rather than giving a wrong answer.
+pub fn run_part_2<A: Answer>(input: &mut File) -> Result<A, NetworkError> {
+    let network = Network::from_file(input)?;
+    let cycles = network.ghost_cycles();
+    if cycles.is_empty() {
+        return Err(NetworkError::NoGhosts);
+    }
+    let periods = cycles
+        .iter()
+        .map(|cycle| {
+            if cycle.goals.is_empty() {
+                return Err(NetworkError::Unreachable(cycle.start.clone()));
+            }
+            cycle
+                .regular_period()
+                .ok_or_else(|| NetworkError::IrregularCycle(cycle.clone()))
+        })
+        .collect::<Result<Vec<u64>, NetworkError>>()?;
+    Ok(lcm(periods)?)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::answer::{BigUint, OverflowError};
+    use crate::common::puzzle_input_reader::test::MockFile;
+
+    use super::{
+        lcm, run_part_1, run_part_2, GhostCycle, Network, NetworkError, ParseNetworkError,
+    };
+
+    const SAMPLE_1: &str = "RL
+
+AAA = (BBB, CCC)
+BBB = (DDD, EEE)
+CCC = (ZZZ, GGG)
+DDD = (DDD, DDD)
+EEE = (EEE, EEE)
+GGG = (GGG, GGG)
+ZZZ = (ZZZ, ZZZ)";
+
+    const SAMPLE_2: &str = "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)";
+
+    fn network(contents: &str) -> Network {
+        let mut input = MockFile::with_contents(contents);
+        Network::from_file(input.get_file()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let cases = [
+            ("", ParseNetworkError::BadInstructions(String::new())),
+            ("LRX", ParseNetworkError::BadInstructions("LRX".to_string())),
+            (
+                "L\nAAA = BBB, CCC",
+                ParseNetworkError::BadNode("AAA = BBB, CCC".to_string()),
+            ),
+            (
+                "L\nAAA = (AAA, AAA)\nAAA = (AAA, AAA)",
+                ParseNetworkError::DuplicateNode("AAA".to_string()),
+            ),
+            (
+                "L\nAAA = (AAA, BBB)",
+                ParseNetworkError::UnknownNode("BBB".to_string()),
+            ),
+        ];
+        for (contents, error) in cases {
+            let mut input = MockFile::with_contents(contents);
+            assert_eq!(
+                Network::from_file(input.get_file()),
+                Err(error),
+                "{contents:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_steps() {
+        let network = network("LLR\n\nAAA = (BBB, BBB)\nBBB = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)");
+        assert_eq!(network.steps("AAA", "ZZZ"), Ok(6));
+        assert_eq!(network.steps("AAA", "AAA"), Ok(0));
+        assert_eq!(
+            network.steps("AAA", "YYY"),
+            Err(NetworkError::MissingNode("YYY".to_string()))
+        );
+        let network = self::network("L\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)");
+        assert_eq!(
+            network.steps("AAA", "ZZZ"),
+            Err(NetworkError::Unreachable("AAA".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_ghost_cycles() {
+        assert_eq!(
+            network(SAMPLE_2).ghost_cycles(),
+            vec![
+                GhostCycle {
+                    start: "11A".to_string(),
+                    tail: 1,
+                    length: 2,
+                    goals: vec![2]
+                },
+                GhostCycle {
+                    start: "22A".to_string(),
+                    tail: 1,
+                    length: 6,
+                    goals: vec![3, 6]
+                },
+            ]
+        );
+        let periods = network(SAMPLE_2)
+            .ghost_cycles()
+            .iter()
+            .map(GhostCycle::regular_period)
+            .collect::<Vec<Option<u64>>>();
+        assert_eq!(periods, vec![Some(2), Some(3)]);
+    }
+
+    #[test]
+    fn test_irregular_cycles() {
+        // The goal comes one step in, but then only every other step.
+        let mut input =
+            MockFile::with_contents("L\n\n11A = (11Z, 11Z)\n11Z = (11B, 11B)\n11B = (11Z, 11Z)");
+        assert_eq!(
+            run_part_2::<u64>(input.get_file()),
+            Err(NetworkError::IrregularCycle(GhostCycle {
+                start: "11A".to_string(),
+                tail: 1,
+                length: 2,
+                goals: vec![1]
+            }))
+        );
+        // The second ghost never reaches a goal at all.
+        let mut input = MockFile::with_contents(
+            "L\n\n11A = (11Z, 11Z)\n11Z = (11Z, 11Z)\n22A = (22B, 22B)\n22B = (22B, 22B)",
+        );
+        assert_eq!(
+            run_part_2::<u64>(input.get_file()),
+            Err(NetworkError::Unreachable("22A".to_string()))
+        );
+        let mut input = MockFile::with_contents("L\n\nAAZ = (AAZ, AAZ)");
+        assert_eq!(
+            run_part_2::<u64>(input.get_file()),
+            Err(NetworkError::NoGhosts)
+        );
+    }
+
+    #[test]
+    fn test_lcm() {
+        assert_eq!(lcm::<u64>([4, 6, 10]), Ok(60));
+        assert_eq!(lcm::<u64>([8, 4, 2]), Ok(8));
+        assert_eq!(lcm::<u64>([]), Ok(1));
+        let primes = [4294967291, 4294967279, 4294967231];
+        assert_eq!(lcm::<u64>(primes), Err(OverflowError { type_name: "u64" }));
+        assert_eq!(
+            lcm::<BigUint>(primes).unwrap().to_string(),
+            "79228160909397609687688407659"
+        );
+    }
+
+    #[test]
+    fn test_sample_input_1() {
+        let mut sample_input = MockFile::with_contents(SAMPLE_1);
+        let actual_result = run_part_1::<u64>(sample_input.get_file()).unwrap();
+        let expected_result = 2;
+        assert_eq!(actual_result, expected_result);
+    }
+
+    #[test]
+    fn test_sample_input_2() {
+        let mut sample_input = MockFile::with_contents(SAMPLE_2);
+        let actual_result = run_part_2::<u64>(sample_input.get_file()).unwrap();
+        let expected_result = 6;
+        assert_eq!(actual_result, expected_result);
+    }
+}
@@ -1,17 +1,291 @@
-use rust::{common::puzzle_input_reader::read_file, day1, day2, day3};
+use std::{
+    env,
+    fs::File,
+    io::{self, BufReader},
+};
+
+use rust::{
+    common::{answer::BigUint, puzzle_input_reader::read_file},
+    day1, day2, day3, day4, day5, day6, day7, day8,
+};
+
+/// Runs a solver, totalling in a `BigUint` when `big` is set and in a checked `u64` otherwise,
+/// and formats the answer or the overflow error.
+macro_rules! answer {
+    ($big:expr, $($solver:ident)::+ ($($arg:expr),*)) => {
+        if $big {
+            $($solver)::+::<BigUint>($($arg),*).map(|a| a.to_string())
+        } else {
+            $($solver)::+::<u64>($($arg),*).map(|a| a.to_string())
+        }
+        .unwrap_or_else(|e| e.to_string())
+    };
+}
+
+/// The flags handled outside of [`day2_bag`], so that anything else starting with `--` can be
+/// rejected as a mistyped bag flag.
+const OTHER_FLAGS: [&str; 14] = [
+    "--big",
+    "--lenient",
+    "--report",
+    "--emit-possible",
+    "--json",
+    "--budget",
+    "--stats",
+    "--stream",
+    "--graph",
+    "--clusters",
+    "--tokens",
+    "--render",
+    "--rulers",
+    "--window",
+];
+
+/// Builds the day 2 bag from `--bag-file <path>` and any `--red`, `--green`, `--blue <n>` or
+/// `--limit <colour> <n>` overrides, falling back to the puzzle's 12 red, 13 green and 14 blue
+/// cubes.
+///
+/// # Panics
+///
+/// On any unknown flag, so that a typo such as `--gren 5` does not quietly solve with the wrong
+/// bag.
+fn day2_bag(args: &[String]) -> day2::Bag {
+    let mut bag = day2::Bag::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let colour = match arg.as_str() {
+            "--bag-file" => {
+                let path = args.next().expect("--bag-file needs a path");
+                bag = day2::Bag::from_file(&mut read_file(path)).unwrap();
+                continue;
+            }
+            "--red" => day2::Colour::RED,
+            "--green" => day2::Colour::GREEN,
+            "--blue" => day2::Colour::BLUE,
+            "--limit" => day2::Colour::intern(args.next().expect("--limit needs a colour")),
+            flag if flag.starts_with("--") && !OTHER_FLAGS.contains(&flag) => {
+                panic!("Unknown flag {flag}")
+            }
+            _ => continue,
+        };
+        let limit = args
+            .next()
+            .and_then(|n| n.parse().ok())
+            .unwrap_or_else(|| panic!("{arg} needs a cube count"));
+        bag.set_limit(colour, limit);
+    }
+    bag
+}
+
+/// Opens a puzzle input, or gives `None` if it has not been downloaded, so that later days can be
+/// skipped.
+fn puzzle(file_name: &str) -> Option<File> {
+    File::open(file_name).ok()
+}
+
+/// Parses a `start..end` range for `--window`.
+fn parse_range(range: &str) -> std::ops::Range<usize> {
+    let (start, end) = range
+        .split_once("..")
+        .unwrap_or_else(|| panic!("{range} is not a start..end range"));
+    start.parse().expect("bad range start")..end.parse().expect("bad range end")
+}
 
 fn main() {
+    let args = env::args().skip(1).collect::<Vec<String>>();
+    let big = args.iter().any(|arg| arg == "--big");
+
     let mut input1_1 = read_file("puzzles/day1-1.txt");
     let mut input1_2 = read_file("puzzles/day1-1.txt");
-    println!("Day1-1: {}", day1::run_part_1(&mut input1_1));
-    println!("Day1-2: {}", day1::run_part_2(&mut input1_2));
+    println!("Day1-1: {}", answer!(big, day1::run_part_1(&mut input1_1)));
+    println!("Day1-2: {}", answer!(big, day1::run_part_2(&mut input1_2)));
 
+    let bag = day2_bag(&args);
+    let mode = if args.iter().any(|arg| arg == "--lenient") {
+        day2::ParseMode::Lenient
+    } else {
+        day2::ParseMode::Strict
+    };
     let mut input2_1 = read_file("puzzles/day2.txt");
     let mut input2_2 = read_file("puzzles/day2.txt");
-    println!("Day2-1: {}", day2::run_part_1(&mut input2_1));
-    println!("Day2-2: {}", day2::run_part_2(&mut input2_2));
-    let mut input3_1 = read_file("puzzles/day3.txt");
-    let mut input3_2 = read_file("puzzles/day3.txt");
-    println!("Day3-1: {}", day3::run_part_1(&mut input3_1));
-    println!("Day3-2: {}", day3::run_part_2(&mut input3_2));
+    println!(
+        "Day2-1: {}",
+        answer!(big, day2::run_part_1(&mut input2_1, &bag, mode))
+    );
+    println!(
+        "Day2-2: {}",
+        answer!(big, day2::run_part_2(&mut input2_2, mode))
+    );
+    if args.iter().any(|arg| arg == "--report") {
+        let mut input2_report = read_file("puzzles/day2.txt");
+        match day2::run_part_1_report(&mut input2_report, &bag, mode) {
+            Ok(report) => {
+                for game in report {
+                    for violation in game.violations {
+                        println!("Game {}: {}", game.id, violation);
+                    }
+                }
+            }
+            Err(error) => println!("Day2 report: {error}"),
+        }
+    }
+    if args.iter().any(|arg| arg == "--emit-possible") {
+        let format = if args.iter().any(|arg| arg == "--json") {
+            day2::GameFormat::Json
+        } else {
+            day2::GameFormat::Text
+        };
+        let mut input2_emit = read_file("puzzles/day2.txt");
+        let possible_games = day2::read_games(&mut input2_emit, mode)
+            .unwrap()
+            .into_iter()
+            .filter(|game| game.is_possible(&bag))
+            .collect::<Vec<day2::Game>>();
+        day2::write_games(&possible_games, format, &mut io::stdout()).unwrap();
+    }
+    if let Some(budget) = args.iter().position(|arg| arg == "--budget").map(|i| {
+        args.get(i + 1)
+            .and_then(|n| n.parse::<u32>().ok())
+            .expect("--budget needs a cube count")
+    }) {
+        let mut input2_budget = read_file("puzzles/day2.txt");
+        let games = day2::read_games(&mut input2_budget, mode).unwrap();
+        let analysis = day2::BagAnalysis::new(&games);
+        if let Some(best) = analysis.best_bag(budget, day2::Objective::IdSum) {
+            println!(
+                "Best bag of {budget} cubes: {} (id sum {})",
+                best.bag, best.score
+            );
+        }
+    }
+    if args.iter().any(|arg| arg == "--stats") {
+        let mut input2_stats = read_file("puzzles/day2.txt");
+        let games = day2::read_games(&mut input2_stats, mode).unwrap();
+        let stats = day2::GameStats::new(&games, &bag);
+        if args.iter().any(|arg| arg == "--json") {
+            println!("{}", stats.to_json());
+        } else {
+            print!("{stats}");
+        }
+    }
+    if args.iter().any(|arg| arg == "--stream") {
+        // A streamed schematic can be arbitrarily tall, so always total it in a BigUint.
+        let input3 = BufReader::new(read_file("puzzles/day3.txt"));
+        match day3::run_streaming::<BigUint>(input3, &day3::SchematicConfig::default()) {
+            Ok((part_1, part_2)) => {
+                println!("Day3-1: {part_1}");
+                println!("Day3-2: {part_2}");
+            }
+            Err(error) => println!("Day3 streaming: {error}"),
+        }
+    } else {
+        let mut input3_1 = read_file("puzzles/day3.txt");
+        let mut input3_2 = read_file("puzzles/day3.txt");
+        println!("Day3-1: {}", answer!(big, day3::run_part_1(&mut input3_1)));
+        println!("Day3-2: {}", answer!(big, day3::run_part_2(&mut input3_2)));
+    }
+    if let Some(format) = args
+        .iter()
+        .position(|arg| arg == "--graph")
+        .map(|i| args.get(i + 1).map_or("dot", String::as_str))
+    {
+        let mut input3_graph = read_file("puzzles/day3.txt");
+        let graph =
+            day3::build_graph(&mut input3_graph, &day3::SchematicConfig::default()).unwrap();
+        match format {
+            "json" => println!("{}", graph.to_json()),
+            _ => print!("{}", graph.to_dot()),
+        }
+    }
+    if args.iter().any(|arg| arg == "--clusters") {
+        let mut input3_clusters = read_file("puzzles/day3.txt");
+        let schematic =
+            day3::Schematic::from_file(&mut input3_clusters, &day3::SchematicConfig::default())
+                .unwrap();
+        for cluster in schematic.clusters() {
+            let bounds = cluster.bounds;
+            println!(
+                "rows {}..={}, cols {}..={}: {} entries, total {}",
+                bounds.first_row,
+                bounds.last_row,
+                bounds.first_col,
+                bounds.last_col,
+                cluster.size(),
+                cluster.total_value::<BigUint>().unwrap()
+            );
+        }
+    }
+    if args.iter().any(|arg| arg == "--tokens") {
+        let mut input3_tokens = read_file("puzzles/day3.txt");
+        let config = day3::SchematicConfig {
+            tokens: Some(day3::TokenConfig::default()),
+            ..Default::default()
+        };
+        match day3::run_tokenized(&mut input3_tokens, &config) {
+            Ok((part_sum, gear_sum)) => println!("Day3 tokenized: {part_sum}, {gear_sum}"),
+            Err(error) => println!("Day3 tokenized: {error}"),
+        }
+    }
+    if args.iter().any(|arg| arg == "--render") {
+        let window = args
+            .iter()
+            .position(|arg| arg == "--window")
+            .map(|i| day3::RenderWindow {
+                rows: parse_range(args.get(i + 1).expect("--window needs a row range")),
+                cols: parse_range(args.get(i + 2).expect("--window needs a column range")),
+            });
+        let options = day3::RenderOptions {
+            rulers: args.iter().any(|arg| arg == "--rulers"),
+            window,
+            ..day3::RenderOptions::default()
+        };
+        let mut input3_render = read_file("puzzles/day3.txt");
+        print!(
+            "{}",
+            day3::render_schematic(
+                &mut input3_render,
+                &day3::SchematicConfig::default(),
+                &options
+            )
+            .unwrap()
+        );
+    }
+
+    if let (Some(mut input4_1), Some(mut input4_2)) =
+        (puzzle("puzzles/day4.txt"), puzzle("puzzles/day4.txt"))
+    {
+        println!("Day4-1: {}", answer!(big, day4::run_part_1(&mut input4_1)));
+        println!("Day4-2: {}", answer!(big, day4::run_part_2(&mut input4_2)));
+    }
+    if let (Some(mut input5_1), Some(mut input5_2)) =
+        (puzzle("puzzles/day5.txt"), puzzle("puzzles/day5.txt"))
+    {
+        match day5::run_part_1(&mut input5_1) {
+            Ok(location) => println!("Day5-1: {location}"),
+            Err(error) => println!("Day5-1: {error}"),
+        }
+        match day5::run_part_2(&mut input5_2) {
+            Ok(Some(location)) => println!("Day5-2: {location}"),
+            Ok(None) => println!("Day5-2: no seeds"),
+            Err(error) => println!("Day5-2: {error}"),
+        }
+    }
+    if let (Some(mut input6_1), Some(mut input6_2)) =
+        (puzzle("puzzles/day6.txt"), puzzle("puzzles/day6.txt"))
+    {
+        println!("Day6-1: {}", answer!(big, day6::run_part_1(&mut input6_1)));
+        println!("Day6-2: {}", answer!(big, day6::run_part_2(&mut input6_2)));
+    }
+    if let (Some(mut input7_1), Some(mut input7_2)) =
+        (puzzle("puzzles/day7.txt"), puzzle("puzzles/day7.txt"))
+    {
+        println!("Day7-1: {}", answer!(big, day7::run_part_1(&mut input7_1)));
+        println!("Day7-2: {}", answer!(big, day7::run_part_2(&mut input7_2)));
+    }
+    if let (Some(mut input8_1), Some(mut input8_2)) =
+        (puzzle("puzzles/day8.txt"), puzzle("puzzles/day8.txt"))
+    {
+        println!("Day8-1: {}", answer!(big, day8::run_part_1(&mut input8_1)));
+        println!("Day8-2: {}", answer!(big, day8::run_part_2(&mut input8_2)));
+    }
 }